@@ -1,10 +1,13 @@
-use colored::*;
+use ethers::signers::{HDPath, Ledger, LedgerError};
 
-use ethers::signers::{HDPath, Ledger};
+use radicle_tools::config;
+use radicle_tools::exit::ExitCode;
+use radicle_tools::output;
 
 pub struct Options {
     pub rpc_url: Option<String>,
     pub testnet: bool,
+    pub json: bool,
 }
 
 pub async fn run(opts: Options) -> anyhow::Result<()> {
@@ -14,16 +17,32 @@ pub async fn run(opts: Options) -> anyhow::Result<()> {
     log::info!("Reading Ledger accounts..");
 
     let ledger = Ledger::new(HDPath::LedgerLive(0), chain_id).await?;
+    let mut accounts = Vec::new();
 
     for i in 0..=8 {
         let path = HDPath::LedgerLive(i);
+        let address = ledger.get_address_with_path(&path).await?;
 
-        println!(
-            "{} {:?}",
-            path.to_string().dimmed(),
-            ledger.get_address_with_path(&path).await?
-        );
+        if opts.json {
+            accounts.push(serde_json::json!({ "path": path.to_string(), "address": address }));
+        } else {
+            println!("{} {:?}", output::dim(&path.to_string()), address);
+        }
     }
 
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&accounts)?);
+    }
     Ok(())
 }
+
+/// Get the exit code of a tool failing with the given error.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    if err.is::<config::Error>() {
+        ExitCode::BadInput
+    } else if err.is::<LedgerError>() {
+        ExitCode::AuthFailure
+    } else {
+        ExitCode::Failure
+    }
+}
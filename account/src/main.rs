@@ -1,53 +1,96 @@
 use std::convert::{TryFrom, TryInto};
-use std::env;
-use std::process;
+use std::io;
 
-use argh::FromArgs;
+use structopt::clap::Shell;
+use structopt::StructOpt;
 
 use rad_account as account;
+use radicle_tools::cli;
+use radicle_tools::config::{env_var, Config};
+use radicle_tools::interrupt;
 use radicle_tools::logger;
+use radicle_tools::output;
 
 /// Work with Ethereum accounts.
-#[derive(FromArgs)]
+#[derive(StructOpt)]
+#[structopt(name = "rad-account")]
 pub struct Options {
+    #[structopt(subcommand)]
+    pub command: Option<Subcommand>,
     /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545)
-    #[argh(option)]
+    #[structopt(long, value_name = "url")]
     pub rpc_url: Option<String>,
-    /// transact on the Ethereum "Rinkeby" testnet (default: false)
-    #[argh(switch)]
+    /// Configuration profile to take defaults from (eg. testnet)
+    #[structopt(long, value_name = "name")]
+    pub profile: Option<String>,
+    /// Transact on the Ethereum "Rinkeby" testnet
+    #[structopt(long)]
     pub testnet: bool,
+    /// Output the accounts as JSON on stdout
+    #[structopt(long)]
+    pub json: bool,
+    /// Only output the accounts
+    #[structopt(short, long)]
+    pub quiet: bool,
+    /// Log level per module, overriding '--quiet' (eg. ethers=debug)
+    #[structopt(long, value_name = "directives")]
+    pub log_filter: Option<logger::Filter>,
 }
 
-impl Options {
-    pub fn from_env() -> Self {
-        argh::from_env()
-    }
+#[derive(StructOpt)]
+pub enum Subcommand {
+    /// Print a completion script for the given shell
+    Completions {
+        #[structopt(possible_values = &Shell::variants())]
+        shell: Shell,
+    },
 }
 
 impl TryFrom<Options> for account::Options {
     type Error = anyhow::Error;
 
     fn try_from(opts: Options) -> anyhow::Result<Self> {
-        let Options { rpc_url, testnet } = opts;
-        let rpc_url = rpc_url.or_else(|| env::var("ETH_RPC_URL").ok());
+        let Options {
+            rpc_url,
+            profile,
+            testnet,
+            json,
+            ..
+        } = opts;
+        let rpc_url = rpc_url
+            .or_else(|| env_var("ETH_RPC_URL"))
+            .or(Config::load_profile(profile.as_deref())?.rpc_url);
 
-        Ok(Self { rpc_url, testnet })
+        Ok(Self {
+            rpc_url,
+            testnet,
+            json,
+        })
     }
 }
 
 #[tokio::main]
 async fn main() {
-    logger::init(env!("CARGO_CRATE_NAME")).unwrap();
-    logger::set_level(log::Level::Info);
+    logger::init_from_env(env!("CARGO_CRATE_NAME")).unwrap();
+    output::init();
+    interrupt::install().unwrap();
 
-    let args = Options::from_env();
+    let mut args: Options = cli::parse_args(env!("CARGO_PKG_VERSION"), &[]);
+    if let Some(Subcommand::Completions { shell }) = args.command {
+        Options::clap().gen_completions_to("rad-account", shell, &mut io::stdout());
+        return;
+    }
+    logger::set_verbosity(false, args.quiet);
+    if let Some(filter) = args.log_filter.take() {
+        logger::set_filter(filter);
+    }
     if let Err(err) = execute(args).await {
         if let Some(cause) = err.source() {
             log::error!("Error: {} ({})", err, cause);
         } else {
             log::error!("Error: {}", err);
         }
-        process::exit(1);
+        account::exit_code(&err).exit();
     }
 }
 
@@ -0,0 +1,272 @@
+//! Git repository access.
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use git2::{BranchType, Commit, ConfigLevel, ErrorCode, ObjectType, Oid, Repository};
+
+/// Git config key holding the default org of a repository.
+pub const CONFIG_ORG: &str = "rad.org";
+/// Git config key holding the default project of a repository.
+pub const CONFIG_PROJECT: &str = "rad.project";
+
+/// Per-repository defaults, stored under the `rad.*` keys of the local git config.
+#[derive(Debug, Default, Clone)]
+pub struct RepoConfig {
+    /// Radicle org the project is anchored under.
+    pub org: Option<String>,
+    /// Radicle project the repository belongs to.
+    pub project: Option<String>,
+}
+
+/// Open the repository to operate on.
+///
+/// When a git directory is given, it is opened as-is, which also works for bare repositories.
+/// Otherwise, `GIT_DIR` is honored if set, and the repository is searched for from the current
+/// directory upwards, so this works from any subdirectory of a working copy.
+pub fn open(git_dir: Option<&Path>) -> Result<Repository, git2::Error> {
+    match git_dir {
+        Some(path) => Repository::open(path),
+        None => Repository::open_from_env(),
+    }
+}
+
+/// Read the per-repository defaults from the local git config.
+pub fn read_config(repo: &Repository) -> Result<RepoConfig, git2::Error> {
+    let config = repo.config()?.open_level(ConfigLevel::Local)?;
+    let get = |key| match config.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    Ok(RepoConfig {
+        org: get(CONFIG_ORG)?,
+        project: get(CONFIG_PROJECT)?,
+    })
+}
+
+/// Write a per-repository default to the local git config.
+pub fn set_config(repo: &Repository, key: &str, value: &str) -> Result<(), git2::Error> {
+    repo.config()?
+        .open_level(ConfigLevel::Local)?
+        .set_str(key, value)
+}
+
+/// Get the `HEAD` commit hash of the given repository.
+pub fn head(repo: &Repository) -> Result<Oid, git2::Error> {
+    let commit = repo.head()?.peel_to_commit()?;
+
+    Ok(commit.id())
+}
+
+/// Get the commits reachable from `to` but not from `from`, newest first.
+///
+/// When `from` is not given, all commits reachable from `to` are returned.
+pub fn commits_between(
+    repo: &Repository,
+    from: Option<Oid>,
+    to: Oid,
+) -> Result<Vec<Commit<'_>>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+
+    walk.push(to)?;
+    if let Some(from) = from {
+        walk.hide(from)?;
+    }
+    walk.map(|oid| repo.find_commit(oid?)).collect()
+}
+
+/// Get the commits pointed to by all tags in the repository.
+///
+/// Tags that don't point to a commit are skipped.
+pub fn tagged_commits(repo: &Repository) -> Result<Vec<Oid>, git2::Error> {
+    let mut commits = Vec::new();
+
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let object = repo.revparse_single(&format!("refs/tags/{}", name))?;
+
+        if let Ok(commit) = object.peel_to_commit() {
+            commits.push(commit.id());
+        }
+    }
+    Ok(commits)
+}
+
+/// Verify that the given commit hash refers to a commit in the repository, and that
+/// this commit is reachable from at least one local branch.
+pub fn verify_commit(repo: &Repository, commit: &str) -> anyhow::Result<()> {
+    let oid = Oid::from_str(commit).map_err(|_| anyhow!("invalid commit hash '{}'", commit))?;
+    let object = repo
+        .find_object(oid, None)
+        .map_err(|_| anyhow!("commit {} was not found in the local repository", commit))?;
+
+    if object.kind() != Some(ObjectType::Commit) {
+        bail!("object {} is not a commit", commit);
+    }
+    if !is_reachable(repo, oid)? {
+        bail!("commit {} is not reachable from any local branch", commit);
+    }
+    Ok(())
+}
+
+/// Check whether a commit is reachable from any local branch.
+fn is_reachable(repo: &Repository, oid: Oid) -> Result<bool, git2::Error> {
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+
+        if let Some(tip) = branch.get().target() {
+            if tip == oid || repo.graph_descendant_of(tip, oid)? {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    /// Create a repository with a branch of two commits, and a commit on no branch.
+    fn setup(name: &str) -> (Repository, [Oid; 3]) {
+        let dir = env::temp_dir().join(format!("radicle-tools-git-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repo = Repository::init(&dir).unwrap();
+        let oids = {
+            let sig = git2::Signature::now("anchor", "anchor@radicle.xyz").unwrap();
+            let tree = repo
+                .find_tree(repo.index().unwrap().write_tree().unwrap())
+                .unwrap();
+            let root = repo
+                .commit(Some("HEAD"), &sig, &sig, "root", &tree, &[])
+                .unwrap();
+            let parent = repo.find_commit(root).unwrap();
+            let tip = repo
+                .commit(Some("HEAD"), &sig, &sig, "tip", &tree, &[&parent])
+                .unwrap();
+            let dangling = repo
+                .commit(None, &sig, &sig, "dangling", &tree, &[&parent])
+                .unwrap();
+
+            [root, tip, dangling]
+        };
+
+        (repo, oids)
+    }
+
+    fn teardown(repo: Repository) {
+        fs::remove_dir_all(repo.path().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let (repo, [root, tip, dangling]) = setup("reachable");
+
+        assert!(is_reachable(&repo, root).unwrap());
+        assert!(is_reachable(&repo, tip).unwrap());
+        assert!(!is_reachable(&repo, dangling).unwrap());
+
+        teardown(repo);
+    }
+
+    #[test]
+    fn test_verify_commit() {
+        let (repo, [root, _, dangling]) = setup("verify");
+        let blob = repo.blob(b"anchor").unwrap();
+
+        assert!(verify_commit(&repo, &root.to_string()).is_ok());
+        assert!(verify_commit(&repo, "ee61b4ff").is_err());
+        assert!(verify_commit(&repo, &Oid::zero().to_string()).is_err());
+        assert!(verify_commit(&repo, &blob.to_string()).is_err());
+        assert!(verify_commit(&repo, &dangling.to_string()).is_err());
+
+        teardown(repo);
+    }
+
+    #[test]
+    fn test_commits_between() {
+        let (repo, [root, tip, _]) = setup("between");
+        let ids = |from| -> Vec<Oid> {
+            commits_between(&repo, from, tip)
+                .unwrap()
+                .iter()
+                .map(|commit| commit.id())
+                .collect()
+        };
+
+        // Without a previous anchor, all commits are returned, newest first.
+        assert_eq!(ids(None), vec![tip, root]);
+        assert_eq!(ids(Some(root)), vec![tip]);
+        assert!(ids(Some(tip)).is_empty());
+
+        teardown(repo);
+    }
+
+    #[test]
+    fn test_tagged_commits() {
+        let (repo, [root, tip, _]) = setup("tags");
+        let sig = git2::Signature::now("anchor", "anchor@radicle.xyz").unwrap();
+        let blob = repo.blob(b"anchor").unwrap();
+
+        // Annotated tags are peeled to the commit they point to.
+        repo.tag(
+            "v1.0.0",
+            &repo.find_object(root, None).unwrap(),
+            &sig,
+            "v1.0.0",
+            false,
+        )
+        .unwrap();
+        repo.tag_lightweight("v1.1.0", &repo.find_object(tip, None).unwrap(), false)
+            .unwrap();
+        // Tags pointing to anything but a commit are skipped.
+        repo.tag_lightweight("blob", &repo.find_object(blob, None).unwrap(), false)
+            .unwrap();
+
+        let mut commits = tagged_commits(&repo).unwrap();
+        commits.sort();
+        let mut expected = vec![root, tip];
+        expected.sort();
+
+        assert_eq!(commits, expected);
+
+        teardown(repo);
+    }
+
+    #[test]
+    fn test_config() {
+        let (repo, _) = setup("config");
+        let config = read_config(&repo).unwrap();
+
+        assert!(config.org.is_none());
+        assert!(config.project.is_none());
+
+        set_config(
+            &repo,
+            CONFIG_ORG,
+            "0x2f94AeA3f1760ECC7A38876A9662986385A80552",
+        )
+        .unwrap();
+        set_config(
+            &repo,
+            CONFIG_PROJECT,
+            "rad:git:hnrkemobagsicpf9sr95o3g551otspcd84c9o",
+        )
+        .unwrap();
+        let config = read_config(&repo).unwrap();
+
+        assert_eq!(
+            config.org.as_deref(),
+            Some("0x2f94AeA3f1760ECC7A38876A9662986385A80552")
+        );
+        assert_eq!(
+            config.project.as_deref(),
+            Some("rad:git:hnrkemobagsicpf9sr95o3g551otspcd84c9o")
+        );
+
+        teardown(repo);
+    }
+}
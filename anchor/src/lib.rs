@@ -1,7 +1,8 @@
-use std::convert::{Infallible, TryFrom, TryInto};
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::io::{BufRead, Write as _};
 use std::path::PathBuf;
 
-use anyhow::Context;
 use anyhow::{anyhow, bail};
 
 use multihash::derive::Multihash;
@@ -11,29 +12,46 @@ use multihash::{MultihashDigest, Sha1Digest, U20, U32};
 use coins_bip32::path::DerivationPath;
 
 use ethers::{
-    abi::{Abi, Detokenize},
+    abi::{Abi, ParamType, Token},
     contract::Contract,
-    prelude::{builders::ContractCall, Bytes, JsonRpcClient, Signer, SignerMiddleware, U256},
-    providers::{Http, Provider},
-    signers::{HDPath, Ledger, LocalWallet},
+    prelude::{Bytes, JsonRpcClient, Signer, SignerMiddleware},
+    providers::Provider,
+    types::{Filter, Log, H256, U64},
 };
 
 use ethers::prelude::Middleware;
 
+use serde_json::json;
+
 pub use ethers::types::Address;
 pub use link_identities::git::Urn;
 
+use radicle_tools::config::Chain;
+use radicle_tools::dry_run;
+use radicle_tools::ethereum::{self, Network, Wallet};
+use radicle_tools::exit::ExitCode;
+use radicle_tools::explorer::Frontend;
+use radicle_tools::output;
+use radicle_tools::pager::Pager;
 use safe_transaction_client as safe;
 
+pub mod git;
+pub mod seed;
+
 /// Anchor options.
 #[derive(Debug, Clone)]
 pub struct Options {
     /// Radicle org under which to anchor the project.
     pub org: Address,
+    /// Org configured as the default, if any, so that anchoring under another org stands out
+    /// in the review.
+    pub default_org: Option<Address>,
     /// Radicle project to anchor.
     pub project: Urn,
-    /// Project commit hash to anchor.
-    pub commit: String,
+    /// Project commit hash to anchor (default: the project head on `seed`).
+    pub commit: Option<String>,
+    /// Seed node to take the project head to anchor from, if no commit is given.
+    pub seed: Option<String>,
     /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545).
     pub rpc_url: String,
     /// Account derivation path when using a Ledger hardware wallet.
@@ -42,38 +60,70 @@ pub struct Options {
     pub keystore: Option<PathBuf>,
     /// Execute a dry run.
     pub dry_run: bool,
+    /// Block explorer URL to link transactions to (default: based on the chain).
+    pub explorer_url: Option<String>,
+    /// Settings per chain, from the configuration file.
+    pub chains: HashMap<String, Chain>,
+    /// Open the transaction in the block explorer once it is sent.
+    pub open: bool,
+    /// Link template of a project commit in a web frontend (default: none).
+    pub commit_url: Option<String>,
+    /// Output the result, or the execution plan of a dry run, as JSON.
+    pub json: bool,
+    /// Submit the transaction without asking for confirmation.
+    pub yes: bool,
 }
 
-const PROJECT_COMMIT_ANCHOR: u32 = 0x0;
-const ORG_ABI: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/abis/OrgV1.json"));
-
-/// Ethereum network.
-#[derive(Debug)]
-enum Network {
-    Homestead,
-    Rinkeby,
+/// Verify options.
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// Radicle org under which the project is anchored.
+    pub org: Address,
+    /// Radicle project to verify anchors of.
+    pub project: Urn,
+    /// Project commit hashes to verify.
+    pub commits: Vec<String>,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545).
+    pub rpc_url: String,
+    /// Block to start looking for anchors from (default: from the chain settings, or 0).
+    pub from_block: Option<u64>,
+    /// Settings per chain, from the configuration file.
+    pub chains: HashMap<String, Chain>,
+    /// Output the result as JSON.
+    pub json: bool,
+    /// Page the result when writing to a terminal.
+    pub pager: bool,
+    /// Link template of a project commit in a web frontend (default: none).
+    pub commit_url: Option<String>,
 }
 
-impl Network {
-    const fn safe_transaction_url(&self) -> &'static str {
-        match self {
-            Self::Homestead => "https://safe-transaction.gnosis.io",
-            Self::Rinkeby => "https://safe-transaction.rinkeby.gnosis.io",
-        }
-    }
+/// Diff options.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Radicle org under which the project is anchored.
+    pub org: Address,
+    /// Radicle project to compare against.
+    pub project: Urn,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545).
+    pub rpc_url: String,
+    /// Path to the git repository (default: current repository).
+    pub git_dir: Option<PathBuf>,
+    /// Output the result as JSON.
+    pub json: bool,
+    /// Page the result when writing to a terminal.
+    pub pager: bool,
+    /// Link template of a project commit in a web frontend (default: none).
+    pub commit_url: Option<String>,
 }
 
-impl TryFrom<u64> for Network {
-    type Error = ();
-
-    fn try_from(other: u64) -> Result<Self, ()> {
-        match other {
-            1 => Ok(Self::Homestead),
-            4 => Ok(Self::Rinkeby),
-            _ => Err(()),
-        }
-    }
-}
+const PROJECT_COMMIT_ANCHOR: u32 = 0x0;
+const ORG_ABI: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/abis/OrgV1.json"));
+/// Signature of the event emitted by the org contract when anchoring.
+const ANCHORED_EVENT: &str = "Anchored(bytes32,uint32,bytes)";
+/// Number of blocks to query logs for in a single request.
+const LOG_BLOCK_RANGE: u64 = 10_000;
+/// Maximum number of concurrent JSON-RPC requests.
+const MAX_CONCURRENT_REQUESTS: usize = 8;
 
 #[derive(Clone, Copy, Debug, Eq, Multihash, PartialEq)]
 #[mh(alloc_size = U32)]
@@ -84,67 +134,86 @@ pub enum Code {
 
 /// Anchor error.
 #[derive(thiserror::Error, Debug)]
-#[allow(clippy::large_enum_variant)]
-pub enum Error<S: std::error::Error> {
-    /// No wallet specified.
-    #[error("no wallet specified")]
-    NoWallet,
-    /// Gnosis Safe error.
-    #[error("safe transaction error: {0}")]
-    Safe(#[from] safe::Error),
-    /// Signature error.
-    #[error("signer error: {0}")]
-    Signer(S),
+pub enum Error {
+    /// The commit is already the latest anchor of the project.
+    #[error("commit {0} is already the latest anchor of the project")]
+    AlreadyAnchored(String),
 }
 
-pub async fn run(opts: Options) -> anyhow::Result<()> {
-    let provider =
-        Provider::<Http>::try_from(opts.rpc_url.as_str()).context("JSON-RPC URL parsing failed")?;
-    let chain_id = provider.get_chainid().await?.as_u64();
-
-    if let Some(keypath) = &opts.keystore {
-        use colored::*;
-
-        log::info!("Decrypting keystore..");
-
-        let prompt = format!("{} Password: ", "??".cyan());
-        let password = rpassword::prompt_password_stdout(&prompt).unwrap();
-        let signer = LocalWallet::decrypt_keystore(keypath, password)
-            .map_err(|_| anyhow!("keystore decryption failed"))?
-            .with_chain_id(chain_id);
+impl Error {
+    /// Get a hint on how to resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::AlreadyAnchored(_) => Some("use 'verify' to check the anchor status of commits"),
+        }
+    }
 
-        log::debug!("Keystore decrypted: {:?}.", signer);
+    /// Get the exit code of a tool failing with this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::AlreadyAnchored(_) => ExitCode::AlreadyDone,
+        }
+    }
+}
 
-        anchor(opts, provider, signer).await
-    } else if let Some(path) = &opts.ledger_hdpath {
-        log::debug!("Connecting to Ledger..");
+/// Get the exit code of a tool failing with the given error.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.exit_code(),
+        None => ethereum::exit_code(err),
+    }
+}
 
-        let hdpath = path.derivation_string();
-        let signer = Ledger::new(HDPath::Other(hdpath), chain_id).await?;
+/// Get a hint on how to resolve the given error, if there is one.
+pub fn hint(err: &anyhow::Error) -> Option<&'static str> {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.hint(),
+        None => ethereum::hint(err),
+    }
+}
 
-        anchor(opts, provider, signer).await
-    } else {
-        Err(anyhow!(Error::<Infallible>::NoWallet))
+pub async fn run(mut opts: Options) -> anyhow::Result<()> {
+    let commit = match (opts.commit.take(), &opts.seed) {
+        (Some(commit), _) => commit,
+        (None, Some(seed)) => seed::project_head(seed, &opts.project)?,
+        (None, None) => bail!("a commit to anchor must be specified with '--commit' or '--seed'"),
+    };
+    let (provider, chain_id) = ethereum::connect(&opts.rpc_url).await?;
+    let wallet = ethereum::wallet(
+        opts.keystore.as_deref(),
+        opts.ledger_hdpath.as_ref(),
+        chain_id,
+    )
+    .await?;
+
+    match wallet {
+        Wallet::Keystore(signer) => anchor(opts, commit, provider, signer).await,
+        Wallet::Ledger(signer) => anchor(opts, commit, provider, signer).await,
     }
 }
 
 async fn anchor<P: 'static + JsonRpcClient + Clone, S: 'static + Signer>(
     opts: Options,
+    commit: String,
     provider: Provider<P>,
     signer: S,
 ) -> anyhow::Result<()> {
     let abi: Abi = serde_json::from_str(ORG_ABI)?;
     let project = opts.project;
-    let commit = opts.commit;
     let chain_id = signer.chain_id();
     let network =
-        Network::try_from(chain_id).map_err(|_| anyhow!("unsupported chain id '{}'", chain_id))?;
+        Network::try_from(chain_id).map_err(|_| ethereum::Error::UnsupportedChain(chain_id))?;
+    let chain = network.config(&opts.chains);
+    let explorer = network.explorer(opts.explorer_url.clone(), &chain, opts.open);
 
     log::info!("Anchoring..");
     log::info!("Chain ID {} ({:?})", chain_id, network);
     log::info!("Radicle ID {}", project);
     log::info!("Org {:?}", opts.org);
-    log::info!("Anchor hash {}", commit);
+    log::info!(
+        "Anchor hash {}",
+        commit_link(opts.commit_url.as_deref(), &project, &commit, &commit)
+    );
     log::info!("Anchor type 'git commit' ({:#x})", PROJECT_COMMIT_ANCHOR);
 
     let contract = Contract::new(opts.org, abi.clone(), provider.clone());
@@ -153,22 +222,10 @@ async fn anchor<P: 'static + JsonRpcClient + Clone, S: 'static + Signer>(
     log::info!("Org owner {:#?}", org_owner);
 
     let safe_client = safe::Client::new(network.safe_transaction_url());
-    let safe = match safe_client.get_safe(org_owner) {
-        Ok(safe) => Some(safe),
-        Err(err) if err.is_not_found() => None,
-        Err(err) => {
-            bail!("request to safe transaction API failed: {:?}", err);
-        }
-    };
+    let safe = ethereum::find_safe(&safe_client, org_owner)?;
 
     // The project id, as a `bytes32`.
-    let id: [u8; 32] = {
-        let bytes = project.id.as_bytes();
-        let mut padded = vec![0; 12];
-
-        padded.extend(bytes);
-        padded.try_into().unwrap()
-    };
+    let id = project_id(&project);
     // The anchor tag as a `uint32`.
     let tag: u32 = PROJECT_COMMIT_ANCHOR;
     // The anchor hash as a `bytes` in multihash format.
@@ -187,66 +244,504 @@ async fn anchor<P: 'static + JsonRpcClient + Clone, S: 'static + Signer>(
         commit.to_bytes().into()
     };
 
-    if opts.dry_run {
-        return Ok(());
+    if latest_anchor(&contract, id).await? == Some(commit.to_lowercase()) {
+        return Err(Error::AlreadyAnchored(commit).into());
+    }
+
+    // The plan is shown for review, unless the transaction was confirmed up-front.
+    if opts.dry_run || !opts.yes {
+        let call = contract
+            .method::<_, ()>("anchor", (id, tag, hash.clone()))?
+            .from(signer.address());
+        let args = json!({ "id": H256::from(id), "tag": tag, "multihash": hash });
+        let safe = safe.as_ref().map(|safe| (org_owner, safe));
+        let spinner = output::spinner("Estimating gas..");
+        let plan = ethereum::plan(
+            &provider,
+            opts.org,
+            "anchor",
+            call,
+            args,
+            signer.address(),
+            &network,
+            safe,
+        )
+        .await?;
+        spinner.finish();
+
+        if opts.dry_run {
+            return Ok(dry_run::print(&plan, opts.json)?);
+        }
+        // There is no default chain or signer, only a configured org.
+        let mut defaults = json!({});
+        if let Some(org) = opts.default_org {
+            defaults["contract"] = json!(org);
+        }
+        dry_run::review(&plan, &defaults)?;
     }
 
+    ethereum::confirm(opts.yes)?;
+
+    let mut result = json!({ "org": opts.org, "project": project.to_string(), "commit": commit });
+
     if let Some(safe) = safe {
         log::info!("Found Gnosis Safe at {}", org_owner);
 
         let call = contract.method::<_, ()>("anchor", (id, tag, hash))?;
         let data = call.calldata().unwrap();
+        let safe_tx_hash = ethereum::propose(opts.org, data, &safe, &signer).await?;
 
-        anchor_safe(opts.org, data, &safe, &signer).await
+        result["safe"] = json!({ "address": org_owner, "safeTxHash": safe_tx_hash });
     } else {
+        ethereum::check_balance(
+            &provider,
+            signer.address(),
+            &network,
+            chain.faucet_url.as_deref(),
+        )
+        .await?;
+
         let signer = SignerMiddleware::new(provider, signer);
         let contract = Contract::new(opts.org, abi, signer);
         let call = contract.method::<_, ()>("anchor", (id, tag, hash))?;
+        let receipt = ethereum::send(call, &explorer).await?;
+
+        log::info!(
+            "Project successfully anchored in block #{} ({})",
+            receipt.block_number.unwrap(),
+            receipt.block_hash.unwrap(),
+        );
+
+        result["transaction"] = json!(receipt.transaction_hash);
+        result["block"] = json!({ "number": receipt.block_number, "hash": receipt.block_hash });
+    }
 
-        anchor_eoa(call).await
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
     }
+    Ok(())
 }
 
-async fn anchor_safe<S: Signer + 'static>(
-    to: Address,
-    data: Bytes,
-    safe: &safe::Safe<'_>,
-    signer: &S,
-) -> anyhow::Result<()> {
-    let safe_tx = safe.create_transaction(to, U256::zero(), data, safe::Operation::Call);
-    let signed_tx = safe_tx
-        .sign(signer)
-        .await
-        .map_err(Error::<S::Error>::Signer)?;
+/// Verify the anchor status of many commits at once.
+pub async fn verify(opts: VerifyOptions) -> anyhow::Result<()> {
+    let (provider, chain_id) = ethereum::connect(&opts.rpc_url).await?;
+    let from_block = opts
+        .from_block
+        .or_else(|| {
+            Network::try_from(chain_id)
+                .ok()
+                .and_then(|network| network.config(&opts.chains).from_block)
+        })
+        .unwrap_or(0);
+    let abi: Abi = serde_json::from_str(ORG_ABI)?;
+    let contract = Contract::new(opts.org, abi, provider.clone());
+    let id = project_id(&opts.project);
 
-    safe.propose(signed_tx)?;
+    log::info!("Verifying {} commit(s)..", opts.commits.len());
+    log::info!("Radicle ID {}", opts.project);
+    log::info!("Org {:?}", opts.org);
 
+    let spinner = output::spinner("Fetching anchors..");
+    let latest = latest_anchor(&contract, id).await?;
+    let anchored = anchored_commits(&provider, opts.org, id, from_block).await?;
+    spinner.finish();
+
+    let mut count = 0;
+    let mut results = Vec::new();
+    let mut out = Pager::start(opts.pager && !opts.json);
+
+    for commit in &opts.commits {
+        let commit = commit.to_lowercase();
+        let block = anchored.get(&commit);
+        let is_latest = latest.as_ref() == Some(&commit);
+
+        if block.is_some() {
+            count += 1;
+        }
+        if opts.json {
+            results.push(json!({
+                "commit": commit,
+                "anchored": block.is_some(),
+                "block": block,
+                "latest": is_latest,
+            }));
+            continue;
+        }
+
+        let status = match block {
+            Some(block) if is_latest => {
+                output::positive(&format!("anchored in block #{} (latest)", block))
+            }
+            Some(block) => output::positive(&format!("anchored in block #{}", block)),
+            None => output::negative("not anchored"),
+        };
+        writeln!(
+            out,
+            "{} {}",
+            commit_link(opts.commit_url.as_deref(), &opts.project, &commit, &commit),
+            status
+        )?;
+    }
+    out.finish()?;
+    log::info!("{} of {} commit(s) anchored", count, opts.commits.len());
+
+    if opts.json {
+        let result = json!({
+            "org": opts.org,
+            "project": opts.project.to_string(),
+            "commits": results,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
     Ok(())
 }
 
-async fn anchor_eoa<M: Middleware + 'static, D: Detokenize>(
-    call: ContractCall<M, D>,
-) -> anyhow::Result<()> {
-    log::info!("Sending transaction..");
+/// Show the commits made since the latest anchor of a project.
+pub async fn diff(opts: DiffOptions) -> anyhow::Result<()> {
+    let provider = ethereum::provider(&opts.rpc_url)?;
+    let abi: Abi = serde_json::from_str(ORG_ABI)?;
+    let contract = Contract::new(opts.org, abi, provider);
+    let repo = git::open(opts.git_dir.as_deref())?;
+    let head = git::head(&repo)?;
+
+    log::info!("Radicle ID {}", opts.project);
+    log::info!("Org {:?}", opts.org);
+
+    let spinner = output::spinner("Fetching latest anchor..");
+    let latest = latest_anchor(&contract, project_id(&opts.project)).await?;
+    spinner.finish();
+
+    let anchor = match latest {
+        Some(commit) => {
+            log::info!(
+                "Latest anchor {}",
+                commit_link(opts.commit_url.as_deref(), &opts.project, &commit, &commit)
+            );
+
+            let oid = git2::Oid::from_str(&commit)?;
+            if repo.find_commit(oid).is_err() {
+                bail!(
+                    "latest anchor {} was not found in the local repository, \
+                    it may have to be fetched first",
+                    commit
+                );
+            }
+            Some(oid)
+        }
+        None => {
+            log::warn!("Project has never been anchored, showing all commits");
+            None
+        }
+    };
+
+    let spinner = output::spinner("Walking git history..");
+    let commits = git::commits_between(&repo, anchor, head)?;
+    spinner.finish();
 
-    let result = loop {
-        let pending = call.send().await?;
-        let tx_hash = *pending;
+    let mut authors: Vec<(String, usize)> = Vec::new();
+    let mut results = Vec::new();
+    let mut out = Pager::start(opts.pager && !opts.json);
 
-        log::info!("Waiting for transaction {:?} to be included..", tx_hash);
+    for commit in &commits {
+        let author = commit.author();
+        let name = author.name().unwrap_or("unknown");
 
-        if let Some(result) = pending.await? {
-            break result;
+        if opts.json {
+            results.push(json!({
+                "commit": commit.id().to_string(),
+                "summary": commit.summary(),
+                "author": name,
+            }));
         } else {
-            log::info!("Transaction {} dropped, retrying..", tx_hash);
+            let id = commit.id().to_string();
+            let short = output::id(&id[..7]).to_string();
+
+            writeln!(
+                out,
+                "{} {} {}",
+                commit_link(opts.commit_url.as_deref(), &opts.project, &id, &short),
+                commit.summary().unwrap_or_default(),
+                output::dim(&format!("({})", name))
+            )?;
         }
-    };
 
+        match authors.iter_mut().find(|(n, _)| n == name) {
+            Some((_, count)) => *count += 1,
+            None => authors.push((name.to_owned(), 1)),
+        }
+    }
+    out.finish()?;
     log::info!(
-        "Project successfully anchored in block #{} ({})",
-        result.block_number.unwrap(),
-        result.block_hash.unwrap(),
+        "{} commit(s) by {} author(s) since the latest anchor",
+        commits.len(),
+        authors.len()
     );
+    for (name, count) in &authors {
+        log::info!("{} {}", count, name);
+    }
 
+    if opts.json {
+        let result = json!({
+            "org": opts.org,
+            "project": opts.project.to_string(),
+            "anchor": anchor.map(|oid| oid.to_string()),
+            "head": head.to_string(),
+            "commits": results,
+            "authors": authors
+                .iter()
+                .map(|(name, count)| json!({ "name": name, "commits": count }))
+                .collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
     Ok(())
 }
+
+/// Link text to a project commit in the web frontend, if there is one.
+fn commit_link(commit_url: Option<&str>, project: &Urn, commit: &str, text: &str) -> String {
+    match commit_url {
+        Some(commit_url) => {
+            let frontend = Frontend {
+                commit_url: commit_url.to_owned(),
+            };
+            output::link(text, &frontend.commit_url(&project.to_string(), commit))
+        }
+        None => text.to_owned(),
+    }
+}
+
+/// Get the commit hash of the latest anchor of a project, if any.
+async fn latest_anchor<M: Middleware + 'static>(
+    contract: &Contract<M>,
+    id: [u8; 32],
+) -> anyhow::Result<Option<String>> {
+    let (tag, hash): (u32, Bytes) = contract.method("anchors", (id,))?.call().await?;
+
+    if tag == PROJECT_COMMIT_ANCHOR {
+        Ok(decode_commit(&hash))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Get all commits anchored for a project, along with the block they were anchored in.
+///
+/// Logs are queried in block ranges starting at `from_block`, with a bounded number of
+/// concurrent requests.
+async fn anchored_commits<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    org: Address,
+    id: [u8; 32],
+    from_block: u64,
+) -> anyhow::Result<HashMap<String, U64>> {
+    use futures::stream::{self, StreamExt as _, TryStreamExt as _};
+
+    let latest = provider.get_block_number().await?.as_u64();
+    let ranges = (from_block..=latest)
+        .step_by(LOG_BLOCK_RANGE as usize)
+        .map(|from| (from, latest.min(from + LOG_BLOCK_RANGE - 1)));
+    let logs: Vec<Vec<Log>> = stream::iter(ranges)
+        .map(|(from, to)| {
+            let filter = Filter::new()
+                .address(org)
+                .event(ANCHORED_EVENT)
+                .from_block(from)
+                .to_block(to);
+
+            async move { provider.get_logs(&filter).await }
+        })
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+    let mut commits = HashMap::new();
+    for log in logs.into_iter().flatten() {
+        let tokens = ethers::abi::decode(
+            &[
+                ParamType::FixedBytes(32),
+                ParamType::Uint(32),
+                ParamType::Bytes,
+            ],
+            log.data.as_ref(),
+        )?;
+
+        if let [Token::FixedBytes(anchor_id), Token::Uint(tag), Token::Bytes(hash)] =
+            tokens.as_slice()
+        {
+            if anchor_id.as_slice() != id || tag.as_u32() != PROJECT_COMMIT_ANCHOR {
+                continue;
+            }
+            if let (Some(commit), Some(block)) = (decode_commit(hash), log.block_number) {
+                commits.insert(commit, block);
+            }
+        }
+    }
+    Ok(commits)
+}
+
+/// Parse a project, given either as a full URN (eg. `rad:git:hnrk..`) or as a bare id.
+pub fn parse_project(input: &str) -> anyhow::Result<Urn> {
+    let input = input.trim();
+    let urn = if input.starts_with("rad:") {
+        input.to_owned()
+    } else {
+        format!("rad:git:{}", input)
+    };
+
+    urn.parse().map_err(|err| {
+        anyhow!(
+            "invalid project id '{}': expected a 'rad:git:' URN or a bare project id ({})",
+            input,
+            err
+        )
+    })
+}
+
+/// Read newline-separated commit hashes, skipping empty lines.
+///
+/// Fails on the first line that isn't a full SHA-1 commit hash.
+pub fn read_commits(reader: impl BufRead) -> anyhow::Result<Vec<String>> {
+    let mut commits = Vec::new();
+
+    for (n, line) in reader.lines().enumerate() {
+        let line = line?;
+        let commit = line.trim();
+
+        if commit.is_empty() {
+            continue;
+        }
+        if !is_commit_hash(commit) {
+            bail!("invalid commit hash '{}' on line {}", commit, n + 1);
+        }
+        commits.push(commit.to_owned());
+    }
+    Ok(commits)
+}
+
+/// Check whether a string is a full SHA-1 commit hash, in either case.
+pub fn is_commit_hash(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Get the project id as a `bytes32`.
+fn project_id(project: &Urn) -> [u8; 32] {
+    let bytes = project.id.as_bytes();
+    let mut padded = vec![0; 12];
+
+    padded.extend(bytes);
+    padded.try_into().unwrap()
+}
+
+/// Decode a commit hash from an anchor multihash.
+fn decode_commit(hash: &[u8]) -> Option<String> {
+    let multihash = multihash::Multihash::from_bytes(hash).ok()?;
+
+    if multihash.code() != u64::from(Code::Sha1) {
+        return None;
+    }
+    Some(
+        multihash
+            .digest()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT_ID: &str = "hnrkemobagsicpf9sr95o3g551otspcd84c9o";
+
+    #[test]
+    fn test_parse_project() {
+        let urn = parse_project(&format!("rad:git:{}", PROJECT_ID)).unwrap();
+
+        assert_eq!(urn.encode_id(), PROJECT_ID);
+        assert_eq!(parse_project(PROJECT_ID).unwrap(), urn);
+        assert_eq!(parse_project(&format!(" {}\n", PROJECT_ID)).unwrap(), urn);
+    }
+
+    #[test]
+    fn test_parse_project_invalid() {
+        assert!(parse_project("").is_err());
+        assert!(parse_project("rad:git:").is_err());
+        assert!(parse_project("hnrk-not-zbase32!").is_err());
+        assert!(parse_project(&format!("rad:eth:{}", PROJECT_ID)).is_err());
+    }
+
+    #[test]
+    fn test_decode_commit() {
+        let commit = "ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd";
+        let mut hash = vec![0x11, 0x14];
+
+        hash.extend(
+            (0..40)
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&commit[i..i + 2], 16).unwrap()),
+        );
+        assert_eq!(decode_commit(&hash).as_deref(), Some(commit));
+
+        // A SHA-256 multihash isn't a commit hash.
+        let mut hash = vec![0x12, 0x20];
+        hash.extend([0; 32].iter());
+        assert_eq!(decode_commit(&hash), None);
+
+        assert_eq!(decode_commit(&[]), None);
+        assert_eq!(decode_commit(&[0x11, 0x14, 0xee]), None);
+    }
+
+    #[test]
+    fn test_read_commits() {
+        let input = "ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd\n\n  \
+                     EE61B4FF3D2FA1B11E2F4C4BBB0FAE8E8B4A36CD  \n";
+
+        assert_eq!(
+            read_commits(input.as_bytes()).unwrap(),
+            vec![
+                "ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd",
+                "EE61B4FF3D2FA1B11E2F4C4BBB0FAE8E8B4A36CD"
+            ]
+        );
+        assert!(read_commits("".as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_commits_invalid() {
+        let err = read_commits("ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd\nee61b4ff\n".as_bytes())
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "invalid commit hash 'ee61b4ff' on line 2");
+        assert!(read_commits("zz61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let code = |err: ethereum::Error| exit_code(&err.into());
+
+        assert_eq!(code(ethereum::Error::NoWallet), ExitCode::BadInput);
+        assert_eq!(code(ethereum::Error::NoTerminal), ExitCode::BadInput);
+        assert_eq!(
+            code(ethereum::Error::KeystoreDecryption),
+            ExitCode::AuthFailure
+        );
+        assert_eq!(code(ethereum::Error::Aborted), ExitCode::UserAbort);
+        assert_eq!(
+            code(ethereum::Error::Reverted("execution reverted".to_owned())),
+            ExitCode::ContractRevert
+        );
+        assert_eq!(
+            exit_code(&Error::AlreadyAnchored("ee61b4ff".to_owned()).into()),
+            ExitCode::AlreadyDone
+        );
+        assert_eq!(exit_code(&anyhow!("unknown")), ExitCode::Failure);
+    }
+
+    #[test]
+    fn test_exit_code_signer() {
+        let err = ethereum::Error::Signer("transaction rejected on device".into());
+
+        assert_eq!(exit_code(&err.into()), ExitCode::AuthFailure);
+    }
+}
@@ -1,173 +1,585 @@
-use std::io::Write;
-use std::process;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::{env, path::PathBuf};
 
-use anyhow::anyhow;
 use anyhow::Context as _;
+use anyhow::{anyhow, bail};
 
 use coins_bip32::path::DerivationPath;
+use structopt::clap::Shell;
+use structopt::StructOpt;
 
 use rad_anchor as anchor;
+use radicle_tools::cli;
+use radicle_tools::config::{env_var, Config};
+use radicle_tools::ethereum;
+use radicle_tools::exit::ExitCode;
+use radicle_tools::interrupt;
 use radicle_tools::logger;
+use radicle_tools::output;
 
 use anchor::{Address, Urn};
 
-const USAGE: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "USAGE"));
 const NAME: &str = env!("CARGO_CRATE_NAME");
+const AFTER_HELP: &str = r#"ENVIRONMENT VARIABLES:
+    RAD_ORG           Radicle org (overwrite with '--org')
+    RAD_PROJECT       Radicle project (overwrite with '--project')
+    RAD_KEYSTORE      Keystore file (overwrite with '--keystore')
+    ETH_RPC_URL       Ethereum JSON-RPC URL (overwrite with '--rpc-url')
+    ETH_HDPATH        Hardware wallet derivation path (overwrite with '--ledger-hdpath')
+    RAD_PROFILE       Configuration profile (overwrite with '--profile')
+    RAD_SEED          Seed node to anchor the project head of (overwrite with '--seed')
+    RAD_EXPLORER_URL  Block explorer URL (overwrite with '--explorer-url')
+    RAD_FROM_BLOCK    Block to start looking for anchors from (overwrite with '--from-block')
+    RAD_COMMIT_URL    Link template of commits in a web frontend, eg.
+                      https://app.radicle.network/{project}/commits/{commit}
+    GIT_DIR           Path to the git repository (overwrite with '--git-dir')
+    PAGER             Pager for long listings, eg. of 'verify' and 'diff' (default: less)
+    NO_COLOR          Disable colored output, which is also disabled when not writing to a terminal
+    RAD_LOG           Log level per module, eg. 'ethers=debug' (overwrite with '--log-filter')
+    RAD_LOG_FORMAT    Log format, either 'text' (default) or 'json' for one JSON object per line
+    RAD_LOG_FILE      File to also write logs to, rotated at 'RAD_LOG_FILE_SIZE' (default: 10M),
+                      keeping 'RAD_LOG_FILE_KEEP' rotated files (default: 5)
+    FORCE_HYPERLINK   Turn terminal hyperlinks of commits and transactions on (1) or off (0)
+
+    Options given on the command line take precedence over environment variables.
+    When running in a terminal, required values that are not given are asked for.
+
+CONFIGURATION:
+    Defaults for '--rpc-url', '--keystore', '--ledger-hdpath' and '--org' are read from
+    ~/.config/radicle-client-tools/config.toml, eg.
+
+        rpc-url = "http://localhost:8545"
+        ledger-hdpath = "m/44'/60'/7'/0/0"
+        org = "0x2f94AeA3f1760ECC7A38876A9662986385A80552"
+        commit-url = "https://app.radicle.network/{project}/commits/{commit}"
+
+    Named profiles override these defaults when selected with '--profile', eg.
+
+        [profile.testnet]
+        rpc-url = "https://rinkeby.infura.io/v3/<project-id>"
+        keystore = "/home/alice/testnet.json"
+
+    Settings per chain apply when the Ethereum node is on that chain, eg.
+
+        [chain.rinkeby]
+        explorer-url = "https://rinkeby.etherscan.io"
+        faucet-url = "https://faucet.example.com"
+        from-block = 8000000
+
+    The org and project may also be set per repository, under the 'rad.org' and
+    'rad.project' keys of the local git config (see 'set-config'). These take
+    precedence over the configuration file, but not over environment variables.
+
+EXAMPLES:
+    rad-anchor --org 0x2f94AeA3f1760ECC7A38876A9662986385A80552 \
+               --project rad:git:hnrkemobagsicpf9sr95o3g551otspcd84c9o \
+               --rpc-url http://localhost:8545 \
+               --ledger-hdpath "m/44'/60'/7'/0/0"
+
+    git rev-list v1.0..v2.0 | rad-anchor verify --stdin \
+               --org 0x2f94AeA3f1760ECC7A38876A9662986385A80552 \
+               --project rad:git:hnrkemobagsicpf9sr95o3g551otspcd84c9o"#;
+
+/// Anchor Radicle projects under an org.
+#[derive(StructOpt)]
+#[structopt(name = "rad-anchor", after_help = AFTER_HELP)]
+struct Args {
+    #[structopt(subcommand)]
+    command: Option<Subcommand>,
+    #[structopt(flatten)]
+    common: Common,
+    /// Project commit hash to anchor
+    #[structopt(long, value_name = "hash")]
+    commit: Option<String>,
+    /// Anchor the project head of a seed node's HTTP API (eg. https://seed.acme.org)
+    #[structopt(long, value_name = "url", conflicts_with = "commit")]
+    seed: Option<String>,
+    /// Account derivation path when using a Ledger hardware wallet
+    #[structopt(long, value_name = "hdpath")]
+    ledger_hdpath: Option<DerivationPath>,
+    /// Keystore file containing encrypted private key (default: none)
+    #[structopt(long, value_name = "file", parse(from_os_str))]
+    keystore: Option<PathBuf>,
+    /// Show what would be done, without signing or sending anything
+    #[structopt(long)]
+    dry_run: bool,
+    /// Block explorer to link transactions to (default: based on the chain)
+    #[structopt(long, value_name = "url")]
+    explorer_url: Option<String>,
+    /// Open sent transactions in the block explorer
+    #[structopt(long)]
+    open: bool,
+    /// Submit transactions without asking for confirmation
+    #[structopt(short, long)]
+    yes: bool,
+    /// Anchor the commit even if it can't be verified locally
+    #[structopt(long)]
+    force: bool,
+}
+
+/// Options shared by all commands.
+#[derive(StructOpt)]
+struct Common {
+    /// Radicle org under which the project is anchored
+    #[structopt(long, value_name = "address")]
+    org: Option<Address>,
+    /// Radicle project, as a URN or bare id
+    #[structopt(long, value_name = "urn", parse(try_from_str = anchor::parse_project))]
+    project: Option<Urn>,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545)
+    #[structopt(long, value_name = "url")]
+    rpc_url: Option<String>,
+    /// Configuration profile to take defaults from (eg. testnet)
+    #[structopt(long, value_name = "name")]
+    profile: Option<String>,
+    /// Path to the git repository, which may be bare (default: current repository)
+    #[structopt(long, value_name = "path", parse(from_os_str))]
+    git_dir: Option<PathBuf>,
+    /// Output the result, or the plan of a dry run, as JSON on stdout
+    #[structopt(long)]
+    json: bool,
+    /// Don't page long listings, even when writing to a terminal
+    #[structopt(long)]
+    no_pager: bool,
+    /// Verbose output
+    #[structopt(short, long, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Only output results, warnings and errors
+    #[structopt(short, long)]
+    quiet: bool,
+    /// Log level per module, overriding '--verbose' and '--quiet' (eg. ethers=debug)
+    #[structopt(long, value_name = "directives")]
+    log_filter: Option<logger::Filter>,
+}
+
+impl Common {
+    /// Fill in the options that were given before the subcommand.
+    fn merge(self, parent: Common) -> Common {
+        Common {
+            org: self.org.or(parent.org),
+            project: self.project.or(parent.project),
+            rpc_url: self.rpc_url.or(parent.rpc_url),
+            profile: self.profile.or(parent.profile),
+            git_dir: self.git_dir.or(parent.git_dir),
+            json: self.json || parent.json,
+            no_pager: self.no_pager || parent.no_pager,
+            verbose: self.verbose || parent.verbose,
+            quiet: self.quiet || parent.quiet,
+            log_filter: self.log_filter.or(parent.log_filter),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+enum Subcommand {
+    /// Verify the anchor status of many commits at once
+    Verify {
+        #[structopt(flatten)]
+        common: Common,
+        /// Verify the commits pointed to by all tags
+        #[structopt(long)]
+        all_tags: bool,
+        /// Read newline-separated commits to verify from stdin
+        #[structopt(long, conflicts_with = "all-tags")]
+        stdin: bool,
+        /// Block to start looking for anchors from, eg. the block the org was created in
+        /// (default: 0)
+        #[structopt(long, value_name = "block")]
+        from_block: Option<u64>,
+    },
+    /// Show the commits made since the latest anchor
+    Diff {
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Store the given org and project as defaults for the current repository
+    SetConfig {
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Print a completion script for the given shell
+    Completions {
+        #[structopt(possible_values = &Shell::variants())]
+        shell: Shell,
+    },
+}
 
 enum Command {
     Run {
         options: anchor::Options,
         verbose: bool,
+        quiet: bool,
+    },
+    Verify {
+        options: anchor::VerifyOptions,
+        verbose: bool,
+        quiet: bool,
+    },
+    Diff {
+        options: anchor::DiffOptions,
+        verbose: bool,
+        quiet: bool,
+    },
+    SetConfig {
+        org: Option<Address>,
+        project: Option<Urn>,
+        git_dir: Option<PathBuf>,
+        json: bool,
+        quiet: bool,
+    },
+    Completions {
+        shell: Shell,
+    },
+}
+
+/// What to do once the options are resolved.
+enum Mode {
+    Anchor,
+    Verify {
+        all_tags: bool,
+        stdin: bool,
+        from_block: Option<u64>,
     },
-    Help,
+    Diff,
+    SetConfig,
 }
 
 fn parse_options() -> anyhow::Result<Command> {
-    use lexopt::prelude::*;
-
-    let mut parser = lexopt::Parser::from_env();
-    let mut verbose = false;
-    let mut org: Option<Address> = None;
-    let mut project: Option<Urn> = None;
-    let mut commit: Option<String> = None;
-    let mut rpc_url: Option<String> = None;
-    let mut keystore: Option<PathBuf> = None;
-    let mut ledger_hdpath: Option<DerivationPath> = None;
-    let mut dry_run = false;
-
-    while let Some(arg) = parser.next()? {
-        match arg {
-            Long("org") => {
-                org = Some(
-                    parser
-                        .value()?
-                        .parse()
-                        .context("invalid value specified for '--org'")?,
-                );
-            }
-            Long("project") => {
-                project = Some(
-                    parser
-                        .value()?
-                        .parse()
-                        .context("invalid value specified for '--project'")?,
-                );
-            }
-            Long("commit") => {
-                commit = Some(parser.value()?.to_string_lossy().to_string());
-            }
-            Long("rpc-url") => {
-                rpc_url = Some(parser.value()?.to_string_lossy().to_string());
-            }
-            Long("keystore") => {
-                keystore = Some(parser.value()?.parse()?);
-            }
-            Long("ledger-hdpath") => {
-                ledger_hdpath = Some(parser.value()?.parse()?);
-            }
-            Long("dry-run") => {
-                dry_run = true;
-            }
-            Long("verbose") | Short('v') => {
-                verbose = true;
-            }
-            Long("help") => {
-                return Ok(Command::Help);
-            }
-            _ => {
-                return Err(anyhow!(arg.unexpected()));
-            }
+    let args: Args = cli::parse_args(env!("CARGO_PKG_VERSION"), &[("chains", ethereum::chains())]);
+    let (mode, common) = match args.command {
+        None => (Mode::Anchor, args.common),
+        Some(Subcommand::Verify {
+            common,
+            all_tags,
+            stdin,
+            from_block,
+        }) => (
+            Mode::Verify {
+                all_tags,
+                stdin,
+                from_block,
+            },
+            common.merge(args.common),
+        ),
+        Some(Subcommand::Diff { common }) => (Mode::Diff, common.merge(args.common)),
+        Some(Subcommand::SetConfig { common }) => (Mode::SetConfig, common.merge(args.common)),
+        Some(Subcommand::Completions { shell }) => return Ok(Command::Completions { shell }),
+    };
+    let Common {
+        org,
+        project,
+        rpc_url,
+        profile,
+        git_dir,
+        json,
+        no_pager,
+        verbose,
+        quiet,
+        log_filter,
+    } = common;
+
+    if let Some(filter) = log_filter {
+        logger::set_filter(filter);
+    }
+
+    if verbose && quiet {
+        bail!("'--verbose' and '--quiet' can't be used together");
+    }
+
+    if let Mode::SetConfig = mode {
+        if org.is_none() && project.is_none() {
+            bail!("a default must be specified with '--org' or '--project'");
         }
+        return Ok(Command::SetConfig {
+            org,
+            project,
+            git_dir,
+            json,
+            quiet,
+        });
     }
 
-    let rpc_url = rpc_url
-        .or_else(|| env::var("ETH_RPC_URL").ok())
-        .and_then(|url| if url.is_empty() { None } else { Some(url) })
-        .ok_or_else(|| {
-            anyhow::anyhow!("An Ethereum JSON-RPC URL must be specified with '--rpc-url'")
-        })?;
+    let config = Config::load_profile(profile.as_deref())?;
+    let repo_config = anchor::git::open(git_dir.as_deref())
+        .ok()
+        .map(|repo| anchor::git::read_config(&repo))
+        .transpose()?
+        .unwrap_or_default();
+    let rpc_url = config.resolve_rpc_url(rpc_url)?;
 
-    let commit = if let Some(commit) = commit {
-        commit
+    let commit_url = env_var("RAD_COMMIT_URL").or(config.commit_url);
+    let default_org = repo_config
+        .org
+        .as_deref()
+        .or(config.org.as_deref())
+        .and_then(|org| Address::from_str(org).ok());
+    let org = match (org, env_var("RAD_ORG"), repo_config.org, config.org) {
+        (Some(org), _, _, _) => org,
+        (None, Some(org), _, _) => {
+            Address::from_str(&org).context("invalid value specified for 'RAD_ORG'")?
+        }
+        (None, None, Some(org), _) => Address::from_str(&org).with_context(|| {
+            format!(
+                "invalid value specified for '{}' in git config",
+                anchor::git::CONFIG_ORG
+            )
+        })?,
+        (None, None, None, Some(org)) => {
+            Address::from_str(&org).context("invalid value specified for 'org' in configuration")?
+        }
+        (None, None, None, None) if output::interactive() => {
+            output::input("Org address", Address::from_str)?
+        }
+        (None, None, None, None) => bail!("an org must be specified with '--org'"),
+    };
+    let project = match (project, env_var("RAD_PROJECT"), repo_config.project) {
+        (Some(project), _, _) => project,
+        (None, Some(project), _) => {
+            anchor::parse_project(&project).context("invalid value specified for 'RAD_PROJECT'")?
+        }
+        (None, None, Some(project)) => anchor::parse_project(&project).with_context(|| {
+            format!(
+                "invalid value specified for '{}' in git config",
+                anchor::git::CONFIG_PROJECT
+            )
+        })?,
+        (None, None, None) if output::interactive() => {
+            output::input("Project URN", anchor::parse_project)?
+        }
+        (None, None, None) => bail!("a project must be specified with '--project'"),
+    };
+
+    match mode {
+        Mode::Anchor => {}
+        Mode::Verify {
+            all_tags,
+            stdin,
+            from_block,
+        } => {
+            let commits = if all_tags {
+                get_tagged_commits(git_dir.as_deref())?
+            } else if stdin {
+                anchor::read_commits(io::stdin().lock())?
+            } else {
+                bail!("the commits to verify must be specified with '--all-tags' or '--stdin'");
+            };
+            let from_block = match from_block {
+                Some(block) => Some(block),
+                None => env_var("RAD_FROM_BLOCK")
+                    .map(|block| block.parse())
+                    .transpose()
+                    .context("invalid value specified for 'RAD_FROM_BLOCK'")?,
+            };
+
+            return Ok(Command::Verify {
+                options: anchor::VerifyOptions {
+                    org,
+                    project,
+                    commits,
+                    rpc_url,
+                    from_block,
+                    chains: config.chains,
+                    json,
+                    pager: !no_pager,
+                    commit_url,
+                },
+                verbose,
+                quiet,
+            });
+        }
+        Mode::Diff => {
+            return Ok(Command::Diff {
+                options: anchor::DiffOptions {
+                    org,
+                    project,
+                    rpc_url,
+                    git_dir,
+                    json,
+                    pager: !no_pager,
+                    commit_url,
+                },
+                verbose,
+                quiet,
+            });
+        }
+        Mode::SetConfig => unreachable!(),
+    }
+
+    // The project head on a seed is only looked up when running, as it takes a request.
+    let seed = args.seed.or_else(|| env_var("RAD_SEED"));
+    let commit = if let Some(commit) = args.commit {
+        verify_commit(&commit, git_dir.as_deref(), args.force)?;
+        Some(commit)
+    } else if seed.is_some() {
+        None
     } else {
-        get_repository_head().map_err(|_| {
+        Some(get_repository_head(git_dir.as_deref()).map_err(|_| {
             anyhow::anyhow!(
                 "repository head could not be retrieved, \
                 please specify anchor hash with '--commit'"
             )
-        })?
+        })?)
     };
 
-    let ledger_hdpath = ledger_hdpath.or_else(|| {
-        env::var("ETH_HDPATH")
-            .ok()
-            .and_then(|v| DerivationPath::from_str(v.as_str()).ok())
-    });
+    let (keystore, ledger_hdpath) = config.resolve_wallet(args.keystore, args.ledger_hdpath)?;
 
     Ok(Command::Run {
         options: anchor::Options {
-            org: org.ok_or_else(|| anyhow!("an org must be specified with '--org'"))?,
-            project: project
-                .ok_or_else(|| anyhow!("a project must be specified with '--project'"))?,
+            org,
+            default_org,
+            project,
             commit,
+            seed,
             rpc_url,
             ledger_hdpath,
             keystore,
-            dry_run,
+            dry_run: args.dry_run,
+            explorer_url: args.explorer_url.or_else(|| env_var("RAD_EXPLORER_URL")),
+            chains: config.chains,
+            open: args.open,
+            commit_url,
+            json,
+            yes: args.yes,
         },
         verbose,
+        quiet,
     })
 }
 
+/// Store the given defaults in the local git config of the current repository.
+fn set_config(
+    org: Option<Address>,
+    project: Option<Urn>,
+    git_dir: Option<&Path>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let repo = anchor::git::open(git_dir)?;
+    let mut result = serde_json::Map::new();
+
+    if let Some(org) = org {
+        let org = format!("{:?}", org);
+
+        anchor::git::set_config(&repo, anchor::git::CONFIG_ORG, &org)?;
+        log::info!("Set {} to {}", anchor::git::CONFIG_ORG, org);
+        result.insert(anchor::git::CONFIG_ORG.to_owned(), org.into());
+    }
+    if let Some(project) = project {
+        let project = project.to_string();
+
+        anchor::git::set_config(&repo, anchor::git::CONFIG_PROJECT, &project)?;
+        log::info!("Set {} to {}", anchor::git::CONFIG_PROJECT, project);
+        result.insert(anchor::git::CONFIG_PROJECT.to_owned(), project.into());
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
 /// Get the `HEAD` commit hash of the current repository.
-fn get_repository_head() -> anyhow::Result<String> {
-    use std::process::Command;
+fn get_repository_head(git_dir: Option<&Path>) -> anyhow::Result<String> {
+    let repo = anchor::git::open(git_dir)?;
+    let head = anchor::git::head(&repo)?;
 
-    let output = Command::new("git").arg("rev-parse").arg("HEAD").output()?;
-    let string = String::from_utf8(output.stdout)?;
-    let hash = string.trim_end().to_owned();
+    Ok(head.to_string())
+}
+
+/// Get the commits pointed to by the tags of the current repository.
+fn get_tagged_commits(git_dir: Option<&Path>) -> anyhow::Result<Vec<String>> {
+    let repo = anchor::git::open(git_dir)?;
+    let commits = anchor::git::tagged_commits(&repo)?;
+
+    Ok(commits.iter().map(|oid| oid.to_string()).collect())
+}
 
-    Ok(hash)
+/// Verify that an explicitly specified commit exists in the current repository.
+///
+/// Verification failures are downgraded to warnings when `force` is set.
+fn verify_commit(commit: &str, git_dir: Option<&Path>, force: bool) -> anyhow::Result<()> {
+    // Without a repository there is nothing to verify the commit against, so only a full
+    // commit hash is accepted, which at least rules out abbreviated hashes.
+    let result = match anchor::git::open(git_dir) {
+        Ok(repo) => anchor::git::verify_commit(&repo, commit),
+        Err(err) if anchor::is_commit_hash(commit) => {
+            log::warn!("Warning: commit {} could not be verified: {}", commit, err);
+            return Ok(());
+        }
+        Err(err) => Err(anyhow!(
+            "commit {} is not a full commit hash, and could not be verified: {}",
+            commit,
+            err
+        )),
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) if force => {
+            log::warn!("Warning: {}", err);
+            Ok(())
+        }
+        Err(err) => Err(anyhow!("{}; use '--force' to anchor it anyway", err)),
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    logger::init(NAME).unwrap();
-    logger::set_level(log::Level::Error);
-
-    match execute().await {
-        Err(err) => {
-            if let Some(&anchor::Error::NoWallet) =
-                err.downcast_ref::<anchor::Error<std::convert::Infallible>>()
-            {
-                log::error!("Error: no wallet specified: either '--ledger-hdpath' or '--keystore' must be specified");
-            } else if let Some(cause) = err.source() {
-                log::error!("Error: {} ({})", err, cause);
-            } else {
-                log::error!("Error: {}", err);
-            }
-            process::exit(1);
-        }
-        Ok(()) => {}
+    logger::init_from_env(NAME).unwrap();
+    output::init();
+    interrupt::install().unwrap();
+
+    let result = match parse_options() {
+        Ok(command) => execute(command)
+            .await
+            .map_err(|err| (anchor::exit_code(&err), err)),
+        Err(err) => Err((ExitCode::BadInput, err)),
+    };
+
+    if let Err((code, err)) = result {
+        code.fail(&err, anchor::hint(&err));
     }
 }
 
-async fn execute() -> anyhow::Result<()> {
-    match parse_options()? {
-        Command::Help => {
-            std::io::stderr().write_all(USAGE)?;
-            return Ok(());
+async fn execute(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Completions { shell } => {
+            Args::clap().gen_completions_to("rad-anchor", shell, &mut io::stdout());
         }
-        Command::Run { options, verbose } => {
-            if verbose {
-                logger::set_level(log::Level::Debug);
-            } else {
-                logger::set_level(log::Level::Info);
-            }
+        Command::Run {
+            options,
+            verbose,
+            quiet,
+        } => {
+            logger::set_verbosity(verbose, quiet);
             anchor::run(options).await?;
         }
+        Command::Verify {
+            options,
+            verbose,
+            quiet,
+        } => {
+            logger::set_verbosity(verbose, quiet);
+            anchor::verify(options).await?;
+        }
+        Command::Diff {
+            options,
+            verbose,
+            quiet,
+        } => {
+            logger::set_verbosity(verbose, quiet);
+            anchor::diff(options).await?;
+        }
+        Command::SetConfig {
+            org,
+            project,
+            git_dir,
+            json,
+            quiet,
+        } => {
+            logger::set_verbosity(false, quiet);
+            set_config(org, project, git_dir.as_deref(), json)?;
+        }
     }
     Ok(())
 }
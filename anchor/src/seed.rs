@@ -0,0 +1,84 @@
+//! Radicle seed node access.
+use anyhow::{anyhow, bail, Context as _};
+
+use crate::Urn;
+
+/// Get the latest head of a project, as seen by a seed node.
+///
+/// The seed is queried through its HTTP API, which only serves heads it has verified.
+pub fn project_head(seed: &str, project: &Urn) -> anyhow::Result<String> {
+    let url = format!("{}/v1/projects/{}", seed.trim_end_matches('/'), project);
+
+    let response: serde_json::Value = ureq::get(&url)
+        .call()
+        .with_context(|| format!("request to seed {} failed", seed))?
+        .into_json()
+        .with_context(|| format!("seed {} returned an invalid response", seed))?;
+    let head = response
+        .get("head")
+        .and_then(|head| head.as_str())
+        .ok_or_else(|| anyhow!("seed {} did not return a head for {}", seed, project))?;
+
+    // The head is anchored as-is, so it must be a full commit hash.
+    if !crate::is_commit_hash(head) {
+        bail!("seed {} returned an invalid head '{}'", seed, head);
+    }
+    Ok(head.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    const PROJECT_ID: &str = "hnrkemobagsicpf9sr95o3g551otspcd84c9o";
+
+    /// Serve a single HTTP response with the given body, and get the URL of the seed.
+    fn serve(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            // Read the request headers, up to the empty line ending them.
+            for line in BufReader::new(&stream).lines() {
+                if line.unwrap().is_empty() {
+                    break;
+                }
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn head(body: &'static str) -> anyhow::Result<String> {
+        let project = crate::parse_project(PROJECT_ID).unwrap();
+
+        project_head(&serve(body), &project)
+    }
+
+    #[test]
+    fn test_project_head() {
+        assert_eq!(
+            head(r#"{ "head": "ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd" }"#).unwrap(),
+            "ee61b4ff3d2fa1b11e2f4c4bbb0fae8e8b4a36cd"
+        );
+    }
+
+    #[test]
+    fn test_project_head_invalid() {
+        assert!(head(r#"{ "name": "radicle-client-tools" }"#).is_err());
+        assert!(head(r#"{ "head": "ee61b4ff" }"#).is_err());
+        assert!(head("<html>Not found</html>").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Exit codes of `rad-anchor`, which scripts rely on.
+use std::env;
+use std::process::{Command, Stdio};
+
+use radicle_tools::ethereum;
+use radicle_tools::exit::ExitCode;
+
+const ORG: &str = "0x2f94AeA3f1760ECC7A38876A9662986385A80552";
+const PROJECT: &str = "hnrkemobagsicpf9sr95o3g551otspcd84c9o";
+const COMMIT: &str = "ee61b4ff7fe2e21fb51f8c6c4a8c1e2d0e8ea3a4";
+/// An RPC URL that nothing listens on.
+const UNREACHABLE_RPC_URL: &str = "http://127.0.0.1:1";
+
+/// Run `rad-anchor` non-interactively, without any configuration, and get its exit code.
+fn rad_anchor(args: &[&str]) -> i32 {
+    let status = Command::new(env!("CARGO_BIN_EXE_rad-anchor"))
+        .args(args)
+        .current_dir(env::temp_dir())
+        .env("XDG_CONFIG_HOME", env::temp_dir().join("rad-anchor-tests"))
+        .env_remove("RAD_ORG")
+        .env_remove("RAD_PROJECT")
+        .env_remove("RAD_KEYSTORE")
+        .env_remove("RAD_PROFILE")
+        .env_remove("ETH_RPC_URL")
+        .env_remove("ETH_HDPATH")
+        .env_remove("GIT_DIR")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+
+    status.code().unwrap()
+}
+
+#[test]
+fn test_help() {
+    assert_eq!(rad_anchor(&["--help"]), i32::from(ExitCode::Success));
+}
+
+#[test]
+fn test_bad_input() {
+    let code = i32::from(ExitCode::BadInput);
+
+    assert_eq!(rad_anchor(&["--no-such-flag"]), code);
+    assert_eq!(rad_anchor(&["--verbose", "--quiet"]), code);
+    assert_eq!(rad_anchor(&["--org", "not-an-address"]), code);
+    assert_eq!(rad_anchor(&["--rpc-url", UNREACHABLE_RPC_URL]), code);
+    assert_eq!(
+        rad_anchor(&["verify", "--all-tags", "--stdin"]),
+        code,
+        "conflicting subcommand options"
+    );
+    assert_eq!(
+        rad_anchor(&[
+            "--org",
+            ORG,
+            "--project",
+            PROJECT,
+            "--commit",
+            &COMMIT[..8],
+            "--rpc-url",
+            UNREACHABLE_RPC_URL,
+        ]),
+        code,
+        "abbreviated commit outside of a repository"
+    );
+}
+
+#[test]
+fn test_rpc_failure() {
+    let code = rad_anchor(&[
+        "--org",
+        ORG,
+        "--project",
+        PROJECT,
+        "--commit",
+        COMMIT,
+        "--force",
+        "--rpc-url",
+        UNREACHABLE_RPC_URL,
+        "--keystore",
+        "keystore.json",
+    ]);
+
+    assert_eq!(code, i32::from(ExitCode::RpcFailure));
+}
+
+#[test]
+fn test_bad_input_no_confirmation() {
+    // Asking for confirmation takes a reachable Ethereum node, so the exit code of failing to
+    // ask is checked directly: scripts omitting '--yes' get the same code as for other
+    // missing input.
+    let err = ethereum::Error::NoConfirmation.into();
+
+    assert_eq!(rad_anchor::exit_code(&err), ExitCode::BadInput);
+}
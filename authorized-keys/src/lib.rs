@@ -125,4 +125,8 @@ pub struct Options {
     /// Optional, the path for parent directory of `.rad/` directory, defaults to `std::env::current_dir()?`.
     #[structopt(short, long, parse(from_os_str))]
     pub dir: Option<PathBuf>,
+
+    /// Optional, output the result as JSON on standard output.
+    #[structopt(long)]
+    pub json: bool,
 }
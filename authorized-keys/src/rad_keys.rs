@@ -68,19 +68,41 @@ impl RadKeys {
             Action::Add => {
                 let (key_id, key) = RadKeys::key_details(&options).await?;
 
-                RadKeys::add(&mut keys, key_type, key_id, key.as_slice()).await?;
+                RadKeys::add(&mut keys, key_type.clone(), key_id.clone(), key.as_slice()).await?;
+
+                if options.json {
+                    let result = serde_json::json!({
+                        "added": { "keyType": key_type.to_string(), "id": key_id },
+                    });
+                    println!("{}", result);
+                }
             }
             Action::Remove => {
                 if let Some(id) = options.id {
-                    RadKeys::remove(&mut keys, key_type, id).await?;
+                    RadKeys::remove(&mut keys, key_type.clone(), id.clone()).await?;
+
+                    if options.json {
+                        let result = serde_json::json!({
+                            "removed": { "keyType": key_type.to_string(), "id": id },
+                        });
+                        println!("{}", result);
+                    }
                 } else {
                     return Err(Error::MissingKeyId);
                 }
             }
             Action::List => {
-                let keys = RadKeys::keyring(&keys, key_type).await?.join(",");
+                let keyring = RadKeys::keyring(&keys, key_type.clone()).await?;
 
-                println!("\tRadicle Authorized Keys: {}", keys);
+                if options.json {
+                    let result = serde_json::json!({
+                        "keyType": key_type.to_string(),
+                        "keys": keyring,
+                    });
+                    println!("{}", result);
+                } else {
+                    println!("\tRadicle Authorized Keys: {}", keyring.join(","));
+                }
             }
         };
 
@@ -178,7 +200,7 @@ impl RadKeyring for RadKeys {
 
         file.write_all(pub_key)?;
 
-        println!("added .rad keys file to: {:?}", path);
+        eprintln!("added .rad keys file to: {:?}", path);
 
         Ok(())
     }
@@ -191,7 +213,7 @@ impl RadKeyring for RadKeys {
         }
         remove_file(&path)?;
 
-        println!("removed .rad keys file: {:?}", path);
+        eprintln!("removed .rad keys file: {:?}", path);
 
         Ok(())
     }
@@ -0,0 +1,175 @@
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::str::FromStr;
+
+use structopt::clap::{App, Shell, SubCommand};
+
+use radicle_tools::config::Config;
+use radicle_tools::exit::ExitCode;
+use radicle_tools::logger;
+use radicle_tools::output;
+use radicle_tools::version;
+
+const USAGE: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "USAGE"));
+const NAME: &str = env!("CARGO_CRATE_NAME");
+
+/// Subcommands, and the executables implementing them.
+///
+/// Other subcommands are dispatched to a `rad-<subcommand>` executable, if one is installed.
+const TOOLS: &[(&str, &str)] = &[
+    ("anchor", "rad-anchor"),
+    ("org", "rad-org"),
+    ("account", "rad-account"),
+    ("proof", "proof-generator"),
+    ("auth-keys", "rad-auth-keys"),
+];
+
+fn main() {
+    logger::init_from_env(NAME).unwrap();
+    output::init();
+
+    match execute() {
+        Ok(code) => process::exit(code),
+        Err(err) => {
+            log::error!("Error: {}", err);
+            ExitCode::Failure.exit();
+        }
+    }
+}
+
+fn execute() -> Result<i32, Box<dyn Error>> {
+    let mut args = env::args_os().skip(1);
+    let command = match args.next() {
+        Some(command) => command.to_string_lossy().to_string(),
+        // Like an unknown command, a missing one is a usage error.
+        None => {
+            io::stderr().write_all(USAGE)?;
+            return Ok(ExitCode::BadInput.into());
+        }
+    };
+
+    let mut args: Vec<OsString> = args.collect();
+
+    if command == "help" || command == "--help" {
+        io::stdout().write_all(USAGE)?;
+        return Ok(0);
+    }
+    if command == "completions" {
+        return completions(&args);
+    }
+    if command == "--version" {
+        println!(
+            "rad-tools {}",
+            version::long(env!("CARGO_PKG_VERSION"), &[])
+        );
+        return Ok(0);
+    }
+
+    // Aliases can't shadow the built-in commands.
+    let command = if TOOLS.iter().any(|(name, _)| *name == command) {
+        command
+    } else if let Some(expansion) = Config::load()?.alias(&command) {
+        log::debug!("Expanding alias '{}' to '{}'", command, expansion.join(" "));
+
+        args.splice(0..0, expansion[1..].iter().map(OsString::from));
+        expansion[0].to_owned()
+    } else {
+        command
+    };
+
+    let program = executable(&command);
+
+    // Interrupts are left to the command, so that its exit code is passed on.
+    ctrlc::set_handler(|| {})?;
+
+    let builtin = TOOLS.iter().find(|(name, _)| *name == command);
+    let status = match Command::new(&program).args(args).status() {
+        Ok(status) => status,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            match builtin {
+                Some((_, name)) => {
+                    log::error!("Error: {} is not installed", name);
+                    eprintln!(
+                        "{}",
+                        output::hint(&format!(
+                            "'{}' runs '{}', which must be installed next to 'rad-tools' or in PATH",
+                            command, name
+                        ))
+                    );
+                }
+                None => log::error!(
+                    "Error: unknown command '{}', see '--help' for usage",
+                    command
+                ),
+            }
+            return Ok(ExitCode::BadInput.into());
+        }
+        Err(err) => {
+            return Err(
+                io::Error::new(err.kind(), format!("{}: {}", program.display(), err)).into(),
+            )
+        }
+    };
+
+    // The exit code of the command is passed on as-is.
+    Ok(status.code().unwrap_or_else(|| ExitCode::Failure.into()))
+}
+
+/// Print a completion script for the shell given as first argument.
+///
+/// Only the subcommands are completed; their options are completed by the scripts of the
+/// tools implementing them.
+fn completions(args: &[OsString]) -> Result<i32, Box<dyn Error>> {
+    let shell = args
+        .first()
+        .and_then(|arg| Shell::from_str(&arg.to_string_lossy()).ok());
+    let shell = match shell {
+        Some(shell) => shell,
+        None => {
+            log::error!(
+                "Error: a shell must be specified, one of: {}",
+                Shell::variants().join(", ")
+            );
+            return Ok(ExitCode::BadInput.into());
+        }
+    };
+
+    let mut app = App::new("rad-tools")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand(SubCommand::with_name("help").about("Show the usage"))
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("Print a completion script for the given shell"),
+        );
+    for (name, program) in TOOLS {
+        app = app.subcommand(SubCommand::with_name(name).about(*program));
+    }
+    app.gen_completions_to("rad-tools", shell, &mut io::stdout());
+
+    Ok(0)
+}
+
+/// Get the executable implementing a subcommand.
+///
+/// Executables installed next to this one take precedence over the ones found in `PATH`.
+fn executable(command: &str) -> PathBuf {
+    let name = TOOLS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, program)| program.to_string())
+        .unwrap_or_else(|| format!("rad-{}", command));
+    let name = format!("{}{}", name, env::consts::EXE_SUFFIX);
+
+    if let Ok(exe) = env::current_exe() {
+        if let Some(path) = exe.parent().map(|dir| dir.join(&name)) {
+            if path.is_file() {
+                return path;
+            }
+        }
+    }
+    PathBuf::from(name)
+}
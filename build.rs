@@ -0,0 +1,26 @@
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    // Record the commit the tools are built from, for `--version`.
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=RADICLE_TOOLS_GIT_COMMIT={}", commit.trim());
+    }
+
+    // Rebuild when a commit is made or checked out. Outside of a git checkout, eg. when
+    // building from a source archive, there's nothing to watch, and cargo's default of
+    // rerunning on any change in the package applies.
+    if Path::new(".git").exists() {
+        // Refs are moved to `packed-refs` by `git gc` and `git pack-refs`.
+        for path in [".git/HEAD", ".git/refs/heads", ".git/packed-refs"] {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
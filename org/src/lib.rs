@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use coins_bip32::path::DerivationPath;
+
+use ethers::{
+    abi::Abi,
+    contract::Contract,
+    prelude::{builders::ContractCall, JsonRpcClient, Signer, SignerMiddleware, U256},
+    providers::Provider,
+};
+
+use serde_json::json;
+
+pub use ethers::types::Address;
+
+use radicle_tools::config::Chain;
+use radicle_tools::dry_run;
+use radicle_tools::ethereum::{self, Network, Wallet};
+use radicle_tools::exit::ExitCode;
+use radicle_tools::output;
+use safe_transaction_client as safe;
+
+/// Org options.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Radicle org to administer.
+    pub org: Address,
+    /// Org configured as the default, if any, so that administering another org stands out
+    /// in the review.
+    pub default_org: Option<Address>,
+    /// Administrative action to perform.
+    pub action: Action,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545).
+    pub rpc_url: String,
+    /// Account derivation path when using a Ledger hardware wallet.
+    pub ledger_hdpath: Option<DerivationPath>,
+    /// Keystore file containing encrypted private key (default: none).
+    pub keystore: Option<PathBuf>,
+    /// Execute a dry run.
+    pub dry_run: bool,
+    /// Block explorer URL to link transactions to (default: based on the chain).
+    pub explorer_url: Option<String>,
+    /// Settings per chain, from the configuration file.
+    pub chains: HashMap<String, Chain>,
+    /// Open the transaction in the block explorer once it is sent.
+    pub open: bool,
+    /// Output the result, or the execution plan of a dry run, as JSON.
+    pub json: bool,
+    /// Submit the transaction without asking for confirmation.
+    pub yes: bool,
+}
+
+/// Org administration action.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Add an owner to the Safe owning the org.
+    AddOwner {
+        owner: Address,
+        /// Signature threshold after adding the owner (default: unchanged).
+        threshold: Option<u64>,
+    },
+    /// Remove an owner from the Safe owning the org.
+    RemoveOwner {
+        owner: Address,
+        /// Signature threshold after removing the owner (default: unchanged, if possible).
+        threshold: Option<u64>,
+    },
+    /// Transfer ownership of the org to a new owner.
+    Transfer { owner: Address },
+}
+
+const ORG_ABI: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/abis/OrgV1.json"));
+const SAFE_ABI: &[&str] = &[
+    "function addOwnerWithThreshold(address owner, uint256 _threshold)",
+    "function removeOwner(address prevOwner, address owner, uint256 _threshold)",
+];
+/// Start of the linked list of owners kept by a Safe.
+const SAFE_SENTINEL_OWNERS: &str = "0x0000000000000000000000000000000000000001";
+
+/// Org error.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The account to add is already an owner of the Safe.
+    #[error("{0:?} is already an owner of Safe {1:?}")]
+    AlreadyOwner(Address, Address),
+    /// The account to remove is not an owner of the Safe.
+    #[error("{0:?} is not an owner of Safe {1:?}")]
+    NotOwner(Address, Address),
+    /// Owners can only be managed if the org is owned by a Safe.
+    #[error("org owner {0:?} is not a Safe")]
+    NotSafe(Address),
+    /// The signature threshold is out of bounds for the number of owners.
+    #[error("invalid threshold {0} for a Safe with {1} owner(s)")]
+    InvalidThreshold(u64, u64),
+}
+
+impl Error {
+    /// Get a hint on how to resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NotSafe(_) => Some("transfer the org to a Safe first with 'owners transfer'"),
+            Self::InvalidThreshold(..) => {
+                Some("the threshold must be at least 1 and at most the number of owners")
+            }
+            Self::AlreadyOwner(..) | Self::NotOwner(..) => None,
+        }
+    }
+
+    /// Get the exit code of a tool failing with this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::AlreadyOwner(..) | Self::NotOwner(..) => ExitCode::AlreadyDone,
+            Self::NotSafe(_) | Self::InvalidThreshold(..) => ExitCode::BadInput,
+        }
+    }
+}
+
+/// Get the exit code of a tool failing with the given error.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.exit_code(),
+        None => ethereum::exit_code(err),
+    }
+}
+
+/// Get a hint on how to resolve the given error, if there is one.
+pub fn hint(err: &anyhow::Error) -> Option<&'static str> {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.hint(),
+        None => ethereum::hint(err),
+    }
+}
+
+pub async fn run(opts: Options) -> anyhow::Result<()> {
+    let (provider, chain_id) = ethereum::connect(&opts.rpc_url).await?;
+    let wallet = ethereum::wallet(
+        opts.keystore.as_deref(),
+        opts.ledger_hdpath.as_ref(),
+        chain_id,
+    )
+    .await?;
+
+    match wallet {
+        Wallet::Keystore(signer) => administer(opts, provider, signer).await,
+        Wallet::Ledger(signer) => administer(opts, provider, signer).await,
+    }
+}
+
+async fn administer<P: 'static + JsonRpcClient + Clone, S: 'static + Signer>(
+    opts: Options,
+    provider: Provider<P>,
+    signer: S,
+) -> anyhow::Result<()> {
+    let abi: Abi = serde_json::from_str(ORG_ABI)?;
+    let chain_id = signer.chain_id();
+    let network =
+        Network::try_from(chain_id).map_err(|_| ethereum::Error::UnsupportedChain(chain_id))?;
+    let chain = network.config(&opts.chains);
+    let explorer = network.explorer(opts.explorer_url.clone(), &chain, opts.open);
+
+    log::info!("Chain ID {} ({:?})", chain_id, network);
+    log::info!("Org {:?}", opts.org);
+
+    let contract = Contract::new(opts.org, abi.clone(), provider.clone());
+
+    let org_owner: Address = contract.method("owner", ())?.call().await?;
+    log::info!("Org owner {:#?}", org_owner);
+
+    let safe_client = safe::Client::new(network.safe_transaction_url());
+    let safe = ethereum::find_safe(&safe_client, org_owner)?;
+
+    let (to, method, call, args) = match &opts.action {
+        Action::Transfer { owner } => {
+            log::info!("Transferring org ownership to {:?}..", owner);
+
+            let call = contract.method::<_, ()>("setOwner", *owner)?;
+
+            (opts.org, "setOwner", call, json!({ "newOwner": owner }))
+        }
+        Action::AddOwner { owner, threshold } => {
+            let safe = safe.as_ref().ok_or(Error::NotSafe(org_owner))?;
+            if safe.owners.contains(owner) {
+                return Err(Error::AlreadyOwner(*owner, org_owner).into());
+            }
+            let threshold = threshold.unwrap_or(safe.threshold);
+
+            check_threshold(threshold, safe.owners.len() as u64 + 1)?;
+
+            log::info!(
+                "Adding owner {:?} to Safe {:?} with threshold {}..",
+                owner,
+                org_owner,
+                threshold
+            );
+
+            let method = "addOwnerWithThreshold";
+            let call = safe_call(
+                &provider,
+                org_owner,
+                method,
+                (*owner, U256::from(threshold)),
+            )?;
+
+            (
+                org_owner,
+                method,
+                call,
+                json!({ "owner": owner, "threshold": threshold }),
+            )
+        }
+        Action::RemoveOwner { owner, threshold } => {
+            let safe = safe.as_ref().ok_or(Error::NotSafe(org_owner))?;
+            let prev_owner =
+                prev_owner(&safe.owners, owner).ok_or(Error::NotOwner(*owner, org_owner))?;
+            let remaining = safe.owners.len() as u64 - 1;
+            let threshold = threshold.unwrap_or_else(|| safe.threshold.min(remaining));
+
+            check_threshold(threshold, remaining)?;
+
+            log::info!(
+                "Removing owner {:?} from Safe {:?} with threshold {}..",
+                owner,
+                org_owner,
+                threshold
+            );
+
+            let method = "removeOwner";
+            let call = safe_call(
+                &provider,
+                org_owner,
+                method,
+                (prev_owner, *owner, U256::from(threshold)),
+            )?;
+
+            (
+                org_owner,
+                method,
+                call,
+                json!({ "prevOwner": prev_owner, "owner": owner, "threshold": threshold }),
+            )
+        }
+    };
+
+    // The plan is shown for review, unless the transaction was confirmed up-front.
+    if opts.dry_run || !opts.yes {
+        let call = call.clone().from(signer.address());
+        let threshold = safe.as_ref().map(|safe| safe.threshold);
+        let safe = safe.as_ref().map(|safe| (org_owner, safe));
+        let spinner = output::spinner("Estimating gas..");
+        let plan = ethereum::plan(
+            &provider,
+            to,
+            method,
+            call,
+            args.clone(),
+            signer.address(),
+            &network,
+            safe,
+        )
+        .await?;
+        spinner.finish();
+
+        if opts.dry_run {
+            return Ok(dry_run::print(&plan, opts.json)?);
+        }
+        // The Safe threshold is unchanged by default. There is no default chain or signer.
+        let mut defaults = json!({ "args": { "threshold": threshold } });
+        if let Some(org) = opts.default_org.filter(|_| to == opts.org) {
+            defaults["contract"] = json!(org);
+        }
+        dry_run::review(&plan, &defaults)?;
+    }
+
+    ethereum::confirm(opts.yes)?;
+
+    let mut result = json!({ "org": opts.org, "contract": to, "method": method, "args": args });
+
+    if let Some(safe) = safe {
+        log::info!("Found Gnosis Safe at {}", org_owner);
+
+        let data = call.calldata().unwrap();
+        let safe_tx_hash = ethereum::propose(to, data, &safe, &signer).await?;
+
+        result["safe"] = json!({ "address": org_owner, "safeTxHash": safe_tx_hash });
+    } else if let Action::Transfer { owner } = opts.action {
+        ethereum::check_balance(
+            &provider,
+            signer.address(),
+            &network,
+            chain.faucet_url.as_deref(),
+        )
+        .await?;
+
+        let signer = SignerMiddleware::new(provider, signer);
+        let contract = Contract::new(opts.org, abi, signer);
+        let call = contract.method::<_, ()>("setOwner", owner)?;
+        let receipt = ethereum::send(call, &explorer).await?;
+
+        log::info!(
+            "Org ownership successfully transferred in block #{} ({})",
+            receipt.block_number.unwrap(),
+            receipt.block_hash.unwrap(),
+        );
+
+        result["transaction"] = json!(receipt.transaction_hash);
+        result["block"] = json!({ "number": receipt.block_number, "hash": receipt.block_hash });
+    } else {
+        return Err(Error::NotSafe(org_owner).into());
+    }
+
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// Get the owner preceding the given one in the linked list of owners kept by a Safe, which
+/// is the sentinel for the first owner. Returns `None` if the account isn't an owner.
+fn prev_owner(owners: &[Address], owner: &Address) -> Option<Address> {
+    match owners.iter().position(|o| o == owner)? {
+        0 => Some(Address::from_str(SAFE_SENTINEL_OWNERS).unwrap()),
+        index => Some(owners[index - 1]),
+    }
+}
+
+/// Check that a Safe signature threshold can be met by the given number of owners.
+fn check_threshold(threshold: u64, owners: u64) -> Result<(), Error> {
+    if threshold == 0 || threshold > owners {
+        return Err(Error::InvalidThreshold(threshold, owners));
+    }
+    Ok(())
+}
+
+/// Build a call to one of the Safe's owner management functions.
+fn safe_call<P: 'static + JsonRpcClient + Clone, T: ethers::abi::Tokenize>(
+    provider: &Provider<P>,
+    safe: Address,
+    method: &str,
+    args: T,
+) -> anyhow::Result<ContractCall<Provider<P>, ()>> {
+    let abi = ethers::abi::parse_abi(SAFE_ABI)?;
+    let contract = Contract::new(safe, abi, provider.clone());
+    let call = contract.method::<_, ()>(method, args)?;
+
+    Ok(call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn address(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    #[test]
+    fn test_prev_owner() {
+        let owners = [address(0xa), address(0xb), address(0xc)];
+        let sentinel = Address::from_str(SAFE_SENTINEL_OWNERS).unwrap();
+
+        assert_eq!(prev_owner(&owners, &address(0xa)), Some(sentinel));
+        assert_eq!(prev_owner(&owners, &address(0xb)), Some(address(0xa)));
+        assert_eq!(prev_owner(&owners, &address(0xc)), Some(address(0xb)));
+        assert_eq!(prev_owner(&owners, &address(0xd)), None);
+        assert_eq!(prev_owner(&[], &address(0xa)), None);
+    }
+
+    #[test]
+    fn test_check_threshold() {
+        assert!(check_threshold(1, 1).is_ok());
+        assert!(check_threshold(2, 3).is_ok());
+        assert!(check_threshold(3, 3).is_ok());
+        assert!(matches!(
+            check_threshold(4, 3),
+            Err(Error::InvalidThreshold(4, 3))
+        ));
+        assert!(matches!(
+            check_threshold(0, 3),
+            Err(Error::InvalidThreshold(0, 3))
+        ));
+        assert!(check_threshold(1, 0).is_err());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let safe = address(0x5afe);
+
+        assert_eq!(
+            exit_code(&Error::AlreadyOwner(address(0xa), safe).into()),
+            ExitCode::AlreadyDone
+        );
+        assert_eq!(exit_code(&Error::NotSafe(safe).into()), ExitCode::BadInput);
+        assert_eq!(
+            exit_code(&Error::InvalidThreshold(4, 3).into()),
+            ExitCode::BadInput
+        );
+        assert_eq!(exit_code(&anyhow!("unknown")), ExitCode::Failure);
+    }
+}
@@ -0,0 +1,281 @@
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::bail;
+use anyhow::Context as _;
+
+use coins_bip32::path::DerivationPath;
+use structopt::clap::Shell;
+use structopt::StructOpt;
+
+use rad_org as org;
+use radicle_tools::cli;
+use radicle_tools::config::{env_var, Config};
+use radicle_tools::ethereum;
+use radicle_tools::exit::ExitCode;
+use radicle_tools::interrupt;
+use radicle_tools::logger;
+use radicle_tools::output;
+
+use org::{Action, Address};
+
+const NAME: &str = env!("CARGO_CRATE_NAME");
+const AFTER_HELP: &str = r#"ENVIRONMENT VARIABLES:
+    RAD_ORG           Radicle org (overwrite with '--org')
+    RAD_KEYSTORE      Keystore file (overwrite with '--keystore')
+    ETH_RPC_URL       Ethereum JSON-RPC URL (overwrite with '--rpc-url')
+    ETH_HDPATH        Hardware wallet derivation path (overwrite with '--ledger-hdpath')
+    RAD_PROFILE       Configuration profile (overwrite with '--profile')
+    RAD_EXPLORER_URL  Block explorer URL (overwrite with '--explorer-url')
+    NO_COLOR          Disable colored output, which is also disabled when not writing to a terminal
+    RAD_LOG           Log level per module, eg. 'ethers=debug' (overwrite with '--log-filter')
+    RAD_LOG_FORMAT    Log format, either 'text' (default) or 'json' for one JSON object per line
+    RAD_LOG_FILE      File to also write logs to, rotated at 'RAD_LOG_FILE_SIZE' (default: 10M),
+                      keeping 'RAD_LOG_FILE_KEEP' rotated files (default: 5)
+    FORCE_HYPERLINK   Turn terminal hyperlinks of transactions on (1) or off (0)
+
+    Options given on the command line take precedence over environment variables.
+    When running in a terminal, required values that are not given are asked for.
+
+CONFIGURATION:
+    Defaults for '--rpc-url', '--keystore', '--ledger-hdpath' and '--org' are read from
+    ~/.config/radicle-client-tools/config.toml, eg.
+
+        rpc-url = "http://localhost:8545"
+        ledger-hdpath = "m/44'/60'/7'/0/0"
+        org = "0x2f94AeA3f1760ECC7A38876A9662986385A80552"
+
+    Named profiles override these defaults when selected with '--profile', eg.
+
+        [profile.testnet]
+        rpc-url = "https://rinkeby.infura.io/v3/<project-id>"
+        keystore = "/home/alice/testnet.json"
+
+    Settings per chain apply when the Ethereum node is on that chain, eg.
+
+        [chain.rinkeby]
+        explorer-url = "https://rinkeby.etherscan.io"
+        faucet-url = "https://faucet.example.com"
+
+EXAMPLES:
+    rad-org owners add 0x1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b \
+            --org 0x2f94AeA3f1760ECC7A38876A9662986385A80552 \
+            --rpc-url http://localhost:8545 \
+            --ledger-hdpath "m/44'/60'/7'/0/0""#;
+
+/// Administer Radicle orgs.
+#[derive(StructOpt)]
+#[structopt(name = "rad-org", after_help = AFTER_HELP)]
+enum Args {
+    /// Manage the owners of an org
+    Owners(Owners),
+    /// Print a completion script for the given shell
+    Completions {
+        #[structopt(possible_values = &Shell::variants())]
+        shell: Shell,
+    },
+}
+
+#[derive(StructOpt)]
+enum Owners {
+    /// Add an owner to the Safe owning the org
+    Add {
+        /// Address of the owner to add
+        #[structopt(value_name = "address")]
+        owner: Address,
+        /// Safe signature threshold after adding the owner (default: unchanged)
+        #[structopt(long, value_name = "n")]
+        threshold: Option<u64>,
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Remove an owner from the Safe owning the org
+    Remove {
+        /// Address of the owner to remove
+        #[structopt(value_name = "address")]
+        owner: Address,
+        /// Safe signature threshold after removing the owner (default: unchanged)
+        #[structopt(long, value_name = "n")]
+        threshold: Option<u64>,
+        #[structopt(flatten)]
+        common: Common,
+    },
+    /// Transfer ownership of the org to a new owner (eg. a Safe)
+    Transfer {
+        /// Address of the new owner
+        #[structopt(value_name = "address")]
+        owner: Address,
+        #[structopt(flatten)]
+        common: Common,
+    },
+}
+
+/// Options shared by all commands.
+#[derive(StructOpt)]
+struct Common {
+    /// Radicle org to administer
+    #[structopt(long, value_name = "address")]
+    org: Option<Address>,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545)
+    #[structopt(long, value_name = "url")]
+    rpc_url: Option<String>,
+    /// Configuration profile to take defaults from (eg. testnet)
+    #[structopt(long, value_name = "name")]
+    profile: Option<String>,
+    /// Account derivation path when using a Ledger hardware wallet
+    #[structopt(long, value_name = "hdpath")]
+    ledger_hdpath: Option<DerivationPath>,
+    /// Keystore file containing encrypted private key (default: none)
+    #[structopt(long, value_name = "file", parse(from_os_str))]
+    keystore: Option<PathBuf>,
+    /// Show what would be done, without signing or sending anything
+    #[structopt(long)]
+    dry_run: bool,
+    /// Output the result, or the plan of a dry run, as JSON on stdout
+    #[structopt(long)]
+    json: bool,
+    /// Block explorer to link transactions to (default: based on the chain)
+    #[structopt(long, value_name = "url")]
+    explorer_url: Option<String>,
+    /// Open sent transactions in the block explorer
+    #[structopt(long)]
+    open: bool,
+    /// Submit transactions without asking for confirmation
+    #[structopt(short, long)]
+    yes: bool,
+    /// Verbose output
+    #[structopt(short, long, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Only output results, warnings and errors
+    #[structopt(short, long)]
+    quiet: bool,
+    /// Log level per module, overriding '--verbose' and '--quiet' (eg. ethers=debug)
+    #[structopt(long, value_name = "directives")]
+    log_filter: Option<logger::Filter>,
+}
+
+enum Command {
+    Run {
+        options: org::Options,
+        verbose: bool,
+        quiet: bool,
+    },
+    Completions {
+        shell: Shell,
+    },
+}
+
+fn parse_options() -> anyhow::Result<Command> {
+    let args: Args = cli::parse_args(env!("CARGO_PKG_VERSION"), &[("chains", ethereum::chains())]);
+    let (action, common) = match args {
+        Args::Owners(Owners::Add {
+            owner,
+            threshold,
+            common,
+        }) => (Action::AddOwner { owner, threshold }, common),
+        Args::Owners(Owners::Remove {
+            owner,
+            threshold,
+            common,
+        }) => (Action::RemoveOwner { owner, threshold }, common),
+        Args::Owners(Owners::Transfer { owner, common }) => (Action::Transfer { owner }, common),
+        Args::Completions { shell } => return Ok(Command::Completions { shell }),
+    };
+    let Common {
+        org,
+        rpc_url,
+        profile,
+        ledger_hdpath,
+        keystore,
+        dry_run,
+        json,
+        explorer_url,
+        open,
+        yes,
+        verbose,
+        quiet,
+        log_filter,
+    } = common;
+
+    if let Some(filter) = log_filter {
+        logger::set_filter(filter);
+    }
+
+    let config = Config::load_profile(profile.as_deref())?;
+    let rpc_url = config.resolve_rpc_url(rpc_url)?;
+
+    let (keystore, ledger_hdpath) = config.resolve_wallet(keystore, ledger_hdpath)?;
+
+    let default_org = config
+        .org
+        .as_deref()
+        .and_then(|org| Address::from_str(org).ok());
+    let org = match (org, env_var("RAD_ORG"), config.org) {
+        (Some(org), _, _) => org,
+        (None, Some(org), _) => {
+            Address::from_str(&org).context("invalid value specified for 'RAD_ORG'")?
+        }
+        (None, None, Some(org)) => {
+            Address::from_str(&org).context("invalid value specified for 'org' in configuration")?
+        }
+        (None, None, None) if output::interactive() => {
+            output::input("Org address", Address::from_str)?
+        }
+        (None, None, None) => bail!("an org must be specified with '--org'"),
+    };
+
+    Ok(Command::Run {
+        options: org::Options {
+            org,
+            default_org,
+            action,
+            rpc_url,
+            ledger_hdpath,
+            keystore,
+            dry_run,
+            explorer_url: explorer_url.or_else(|| env_var("RAD_EXPLORER_URL")),
+            chains: config.chains,
+            open,
+            json,
+            yes,
+        },
+        verbose,
+        quiet,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    logger::init_from_env(NAME).unwrap();
+    output::init();
+    interrupt::install().unwrap();
+
+    let result = match parse_options() {
+        Ok(command) => execute(command)
+            .await
+            .map_err(|err| (org::exit_code(&err), err)),
+        Err(err) => Err((ExitCode::BadInput, err)),
+    };
+
+    if let Err((code, err)) = result {
+        code.fail(&err, org::hint(&err));
+    }
+}
+
+async fn execute(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Completions { shell } => {
+            Args::clap().gen_completions_to("rad-org", shell, &mut io::stdout());
+        }
+        Command::Run {
+            options,
+            verbose,
+            quiet,
+        } => {
+            logger::set_verbosity(verbose, quiet);
+            org::run(options).await?;
+        }
+    }
+    Ok(())
+}
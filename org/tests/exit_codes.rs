@@ -0,0 +1,59 @@
+//! Exit codes of `rad-org`, which scripts rely on.
+use std::env;
+use std::process::{Command, Stdio};
+
+use radicle_tools::exit::ExitCode;
+
+const ORG: &str = "0x2f94AeA3f1760ECC7A38876A9662986385A80552";
+const OWNER: &str = "0x1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b";
+/// An RPC URL that nothing listens on.
+const UNREACHABLE_RPC_URL: &str = "http://127.0.0.1:1";
+
+/// Run `rad-org` non-interactively, without any configuration, and get its exit code.
+fn rad_org(args: &[&str]) -> i32 {
+    let status = Command::new(env!("CARGO_BIN_EXE_rad-org"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", env::temp_dir().join("rad-org-tests"))
+        .env_remove("RAD_ORG")
+        .env_remove("RAD_KEYSTORE")
+        .env_remove("RAD_PROFILE")
+        .env_remove("ETH_RPC_URL")
+        .env_remove("ETH_HDPATH")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+
+    status.code().unwrap()
+}
+
+#[test]
+fn test_bad_input() {
+    let code = i32::from(ExitCode::BadInput);
+
+    assert_eq!(rad_org(&[]), code, "missing subcommand");
+    assert_eq!(rad_org(&["owners", "add", "not-an-address"]), code);
+    assert_eq!(
+        rad_org(&["owners", "add", OWNER, "--rpc-url", UNREACHABLE_RPC_URL]),
+        code,
+        "missing org"
+    );
+}
+
+#[test]
+fn test_rpc_failure() {
+    let code = rad_org(&[
+        "owners",
+        "add",
+        OWNER,
+        "--org",
+        ORG,
+        "--rpc-url",
+        UNREACHABLE_RPC_URL,
+        "--keystore",
+        "keystore.json",
+    ]);
+
+    assert_eq!(code, i32::from(ExitCode::RpcFailure));
+}
@@ -2,14 +2,16 @@ use anyhow::anyhow;
 use coins_bip32::path::DerivationPath;
 use ethers::{
     prelude::Signer,
-    providers::{Http, Middleware, Provider},
-    signers::{HDPath, Ledger},
+    providers::Middleware,
     types::{Signature, H160, H256},
 };
+use radicle_tools::dry_run;
+use radicle_tools::ethereum::{self, Wallet};
+use radicle_tools::exit::ExitCode;
+use radicle_tools::output;
 use serde::{Deserialize, Serialize};
 use std::{
     borrow::Borrow,
-    convert::TryFrom,
     fmt::{Debug, Display},
     fs,
     io::Write,
@@ -31,6 +33,10 @@ pub struct Options {
     pub ledger_hdpath: Option<DerivationPath>,
     /// Keystore file containing encrypted private key (default: none).
     pub keystore: Option<PathBuf>,
+    /// Show what would be done, without signing or writing the proof.
+    pub dry_run: bool,
+    /// Output the result as JSON.
+    pub json: bool,
 }
 
 /// Proof that a GPG key belongs to the same person as an Ethereum key.
@@ -50,9 +56,6 @@ pub struct Proof {
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    /// No wallet specified.
-    #[error("no wallet specified")]
-    NoWallet,
     /// Not able to retrieve block .
     #[error("not able to retrieve block")]
     NoBlock,
@@ -67,6 +70,42 @@ pub enum Error {
     GPGSigFailed(String),
 }
 
+impl Error {
+    /// Get a hint on how to resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::GPGSigFailed(_) => {
+                Some("check that '--gpg-key' or 'RAD_GPG_KEY' is a key in your GPG keyring")
+            }
+            Self::NoBlock | Self::NoBlockHash | Self::ETHSigFailed => None,
+        }
+    }
+
+    /// Get the exit code of a tool failing with this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::ETHSigFailed | Self::GPGSigFailed(_) => ExitCode::AuthFailure,
+            Self::NoBlock | Self::NoBlockHash => ExitCode::RpcFailure,
+        }
+    }
+}
+
+/// Get the exit code of a tool failing with the given error.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.exit_code(),
+        None => ethereum::exit_code(err),
+    }
+}
+
+/// Get a hint on how to resolve the given error, if there is one.
+pub fn hint(err: &anyhow::Error) -> Option<&'static str> {
+    match err.downcast_ref::<Error>() {
+        Some(err) => err.hint(),
+        None => ethereum::hint(err),
+    }
+}
+
 /// Sign a message with a GPG private key using the GPG CLI
 fn gpg_sign(key: &str, message: &str) -> anyhow::Result<String> {
     let mut gpg = Command::new("gpg")
@@ -115,10 +154,11 @@ async fn create_proof<T: 'static + Signer>(
 ) -> anyhow::Result<Proof> {
     let msg = create_message(&gpg_key, &signer.address(), block_hash);
 
-    log::info!("Signing message with ETH keypair..");
+    let spinner = output::spinner("Signing message with ETH keypair..");
     let eth_sig = eth_sign(signer, &msg)
         .await
         .map_err(|_| anyhow!(Error::ETHSigFailed))?;
+    spinner.finish();
     log::debug!("ETH Signature: {:?}.", eth_sig);
 
     log::info!("Signing message with GPG keypair..");
@@ -140,42 +180,68 @@ async fn create_proof<T: 'static + Signer>(
 /// - Creates a message that will be signed by the defined signer.
 /// - Write both proofs to a JSON file.
 pub async fn run(opts: Options) -> anyhow::Result<()> {
-    let provider =
-        Provider::<Http>::try_from(opts.rpc_url).expect("could not instantiate HTTP Provider");
-    let latest_block_number = provider.get_block_number().await?;
+    let (provider, chain_id) = ethereum::connect(&opts.rpc_url).await?;
+    let spinner = output::spinner("Fetching block hash..");
+    let latest_block_number = provider
+        .get_block_number()
+        .await
+        .map_err(ethereum::Error::Rpc)?;
     // 5760 blocks earlier is aprox. 1 day ago, this due to avoid referencing blocks that are affected by reorgs of the chain.
     let block_number = latest_block_number.saturating_sub(ethers::prelude::U64::from(5760));
     let block = provider
         .get_block(block_number)
-        .await?
+        .await
+        .map_err(ethereum::Error::Rpc)?
         .ok_or(anyhow!(Error::NoBlock))?;
     let block_hash = block.hash.ok_or(anyhow!(Error::NoBlockHash))?;
-    if let Some(keypath) = &opts.keystore {
-        use colored::*;
-
-        log::info!("Decrypting keystore..");
-        let prompt = format!("{} Password: ", "??".cyan());
-        let password = rpassword::prompt_password_stdout(&prompt).unwrap();
-        let signer = ethers::signers::LocalWallet::decrypt_keystore(keypath, password)
-            .map_err(|_| anyhow!("keystore decryption failed"))?;
-        log::debug!("Keystore decrypted: {:?}.", signer);
+    spinner.finish();
 
-        let proof = create_proof(&opts.gpg_key, &signer, &block_hash).await?;
-        fs::write(&opts.output, serde_json::to_string(&proof)?)?;
+    let wallet = ethereum::wallet(
+        opts.keystore.as_deref(),
+        opts.ledger_hdpath.as_ref(),
+        chain_id,
+    )
+    .await?;
 
-        Ok(())
-    } else if let Some(path) = &opts.ledger_hdpath {
-        log::info!("Connecting to Ledger..");
+    match wallet {
+        Wallet::Keystore(signer) => prove(&signer, &block_hash, &opts).await,
+        Wallet::Ledger(signer) => prove(&signer, &block_hash, &opts).await,
+    }
+}
 
-        let hdpath = path.derivation_string();
-        let signer = Ledger::new(HDPath::Other(hdpath), 1).await?;
-        log::info!("Successfully connected to Ledger..");
+/// Create a proof with the given signer and write it to the output file, or print what would
+/// be signed in case of a dry run.
+async fn prove<S: 'static + Signer>(
+    signer: &S,
+    block_hash: &H256,
+    opts: &Options,
+) -> anyhow::Result<()> {
+    if opts.dry_run {
+        let plan = serde_json::json!({
+            "message": create_message(&opts.gpg_key, &signer.address(), block_hash),
+            "gpgKey": opts.gpg_key,
+            "ethKey": signer.address(),
+            "output": opts.output,
+        });
+        return Ok(dry_run::print(&plan, opts.json)?);
+    }
+    let proof = create_proof(&opts.gpg_key, signer, block_hash).await?;
 
-        let proof = create_proof(&opts.gpg_key, &signer, &block_hash).await?;
-        fs::write(&opts.output, serde_json::to_string(&proof)?)?;
+    write_proof(&proof, opts)
+}
 
-        Ok(())
-    } else {
-        Err(anyhow!(Error::NoWallet))
+/// Write a proof to the output file.
+fn write_proof(proof: &Proof, opts: &Options) -> anyhow::Result<()> {
+    fs::write(&opts.output, serde_json::to_string(proof)?)?;
+    log::info!("Proof successfully created");
+
+    if opts.json {
+        let result = serde_json::json!({
+            "output": opts.output,
+            "gpgKey": proof.gpg_key,
+            "ethKey": proof.eth_key,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
     }
+    Ok(())
 }
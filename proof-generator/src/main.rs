@@ -1,113 +1,186 @@
-use anyhow::anyhow;
+use anyhow::bail;
 use coins_bip32::path::DerivationPath;
 use proof_generator as proof;
+use radicle_tools::cli;
+use radicle_tools::config::{env_var, Config};
+use radicle_tools::exit::ExitCode;
+use radicle_tools::interrupt;
 use radicle_tools::logger;
-use std::env;
-use std::io::Write;
+use radicle_tools::output;
+use std::io;
 use std::path::PathBuf;
-use std::process;
+use std::str::FromStr;
+use structopt::clap::Shell;
+use structopt::StructOpt;
 
-const USAGE: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "USAGE"));
 const NAME: &str = env!("CARGO_CRATE_NAME");
+const AFTER_HELP: &str = r#"ENVIRONMENT VARIABLES:
+    RAD_GPG_KEY     GPG key fingerprint (overwrite with '--gpg-key')
+    RAD_KEYSTORE    Keystore file (overwrite with '--keystore')
+    ETH_RPC_URL     Ethereum JSON-RPC URL (overwrite with '--rpc-url')
+    ETH_HDPATH      Hardware wallet derivation path (overwrite with '--ledger-hdpath')
+    RAD_PROFILE     Configuration profile (overwrite with '--profile')
+    NO_COLOR        Disable colored output, which is also disabled when not writing to a terminal
+    RAD_LOG         Log level per module, eg. 'ethers=debug' (overwrite with '--log-filter')
+    RAD_LOG_FORMAT  Log format, either 'text' (default) or 'json' for one JSON object per line
+    RAD_LOG_FILE    File to also write logs to, rotated at 'RAD_LOG_FILE_SIZE' (default: 10M),
+                    keeping 'RAD_LOG_FILE_KEEP' rotated files (default: 5)
+
+    Options given on the command line take precedence over environment variables.
+    When running in a terminal, required values that are not given are asked for.
+
+CONFIGURATION:
+    Defaults for '--rpc-url', '--keystore' and '--ledger-hdpath' are read from
+    ~/.config/radicle-client-tools/config.toml, eg.
+
+        rpc-url = "http://localhost:8545"
+        ledger-hdpath = "m/44'/60'/0'/0/0"
+
+    Named profiles override these defaults when selected with '--profile', eg.
+
+        [profile.testnet]
+        rpc-url = "https://rinkeby.infura.io/v3/<project-id>"
+        keystore = "/home/alice/testnet.json"
+
+EXAMPLES:
+    proof-generator --gpg-key EB1729638209DCE61281F416504C9C1DE8C47EDF \
+                    --rpc-url http://localhost:8545 \
+                    --ledger-hdpath "m/44'/60'/0'/0/0" \
+                    --output ./proof.json"#;
+
+/// Generate a proof linking a GPG key to an Ethereum account.
+#[derive(StructOpt)]
+#[structopt(name = "proof-generator", after_help = AFTER_HELP)]
+struct Args {
+    #[structopt(subcommand)]
+    command: Option<Subcommand>,
+    /// GPG key fingerprint
+    #[structopt(long, value_name = "string")]
+    gpg_key: Option<String>,
+    /// Path to where the proof should be stored
+    #[structopt(long, value_name = "file", parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545)
+    #[structopt(long, value_name = "url")]
+    rpc_url: Option<String>,
+    /// Configuration profile to take defaults from (eg. testnet)
+    #[structopt(long, value_name = "name")]
+    profile: Option<String>,
+    /// HD Derivation path of Ledger HW
+    #[structopt(long, value_name = "hdpath")]
+    ledger_hdpath: Option<DerivationPath>,
+    /// Path to keystore file
+    #[structopt(long, value_name = "file", parse(from_os_str))]
+    keystore: Option<PathBuf>,
+    /// Show what would be done, without signing or writing the proof
+    #[structopt(long)]
+    dry_run: bool,
+    /// Output the result, or the plan of a dry run, as JSON on stdout
+    #[structopt(long)]
+    json: bool,
+    /// Verbose output
+    #[structopt(short, long, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Only output results, warnings and errors
+    #[structopt(short, long)]
+    quiet: bool,
+    /// Log level per module, overriding '--verbose' and '--quiet' (eg. ethers=debug)
+    #[structopt(long, value_name = "directives")]
+    log_filter: Option<logger::Filter>,
+}
+
+#[derive(StructOpt)]
+enum Subcommand {
+    /// Print a completion script for the given shell
+    Completions {
+        #[structopt(possible_values = &Shell::variants())]
+        shell: Shell,
+    },
+}
 
 enum Command {
     Run {
         options: proof::Options,
         verbose: bool,
+        quiet: bool,
+    },
+    Completions {
+        shell: Shell,
     },
-    Help,
 }
 
 fn parse_options() -> anyhow::Result<Command> {
-    use lexopt::prelude::*;
-
-    let mut parser = lexopt::Parser::from_env();
-    let mut gpg_key: Option<String> = None;
-    let mut output: Option<PathBuf> = None;
-    let mut rpc_url: Option<String> = None;
-    let mut ledger_hdpath: Option<DerivationPath> = None;
-    let mut keystore: Option<PathBuf> = None;
-    let mut verbose = false;
-
-    while let Some(arg) = parser.next()? {
-        match arg {
-            Long("gpg-key") => {
-                gpg_key = Some(parser.value()?.parse()?);
-            }
-            Long("output") => {
-                output = Some(parser.value()?.parse()?);
-            }
-            Long("keystore") => {
-                keystore = Some(parser.value()?.parse()?);
-            }
-            Long("ledger-hdpath") => {
-                ledger_hdpath = Some(parser.value()?.parse()?);
-            }
-            Long("rpc-url") => {
-                rpc_url = Some(parser.value()?.parse()?);
-            }
-            Long("verbose") | Short('v') => {
-                verbose = true;
-            }
-            Long("help") => {
-                return Ok(Command::Help);
-            }
-            _ => {
-                return Err(anyhow!(arg.unexpected()));
-            }
-        }
+    let args: Args = cli::parse_args(env!("CARGO_PKG_VERSION"), &[]);
+
+    if let Some(Subcommand::Completions { shell }) = args.command {
+        return Ok(Command::Completions { shell });
+    }
+
+    if let Some(filter) = args.log_filter {
+        logger::set_filter(filter);
     }
 
+    let config = Config::load_profile(args.profile.as_deref())?;
+    let (keystore, ledger_hdpath) = config.resolve_wallet(args.keystore, args.ledger_hdpath)?;
+
+    let gpg_key = match args.gpg_key.or_else(|| env_var("RAD_GPG_KEY")) {
+        Some(key) => key,
+        None if output::interactive() => output::input("GPG key fingerprint", String::from_str)?,
+        None => bail!("a gpg fingerprint must be specified with '--gpg-key'"),
+    };
+    let rpc_url = config.resolve_rpc_url(args.rpc_url)?;
+    let output = match args.output {
+        Some(path) => path,
+        None if output::interactive() => output::input("Proof output file", PathBuf::from_str)?,
+        None => bail!("an output path must be specified with '--output'"),
+    };
+
     Ok(Command::Run {
         options: proof::Options {
-            gpg_key: gpg_key
-                .ok_or_else(|| anyhow!("a gpg fingerprint must be specified with '--gpg-key'"))?,
-            output: output
-                .ok_or_else(|| anyhow!("an output path must be specified with '--output'"))?,
-            rpc_url: rpc_url
-                .ok_or_else(|| anyhow!("a json rpc provider must be specified with '--rpc-url'"))?,
+            gpg_key,
+            output,
+            rpc_url,
             ledger_hdpath,
             keystore,
+            dry_run: args.dry_run,
+            json: args.json,
         },
-        verbose,
+        verbose: args.verbose,
+        quiet: args.quiet,
     })
 }
 
 #[tokio::main]
 async fn main() {
-    logger::init(NAME).unwrap();
-    logger::set_level(log::Level::Error);
-
-    match execute().await {
-        Err(err) => {
-            if let Some(&proof::Error::NoWallet) = err.downcast_ref() {
-                log::error!("Error: no wallet specified: either '--ledger-hdpath' or '--keystore' must be specified");
-            } else if let Some(cause) = err.source() {
-                log::error!("Error: {} ({})", err, cause);
-            } else {
-                log::error!("Error: {}", err);
-            }
-            process::exit(1);
-        }
-        Ok(()) => {}
+    logger::init_from_env(NAME).unwrap();
+    output::init();
+    interrupt::install().unwrap();
+
+    let result = match parse_options() {
+        Ok(command) => execute(command)
+            .await
+            .map_err(|err| (proof::exit_code(&err), err)),
+        Err(err) => Err((ExitCode::BadInput, err)),
+    };
+
+    if let Err((code, err)) = result {
+        code.fail(&err, proof::hint(&err));
     }
 }
 
-async fn execute() -> anyhow::Result<()> {
-    match parse_options()? {
-        Command::Help => {
-            std::io::stderr().write_all(USAGE)?;
-            return Ok(());
+async fn execute(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Completions { shell } => {
+            Args::clap().gen_completions_to("proof-generator", shell, &mut io::stdout());
         }
-        Command::Run { options, verbose } => {
-            if verbose {
-                logger::set_level(log::Level::Debug);
-            } else {
-                logger::set_level(log::Level::Info);
-            }
+        Command::Run {
+            options,
+            verbose,
+            quiet,
+        } => {
+            logger::set_verbosity(verbose, quiet);
             proof::run(options).await?;
         }
     }
-    log::info!("Proof successfully created");
     Ok(())
 }
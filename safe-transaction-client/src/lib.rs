@@ -80,6 +80,13 @@ pub struct SignedSafeTx {
     signature: Signature,
 }
 
+impl SignedSafeTx {
+    /// Get the hash identifying this transaction in the Safe.
+    pub fn safe_tx_hash(&self) -> TxHash {
+        self.safe_tx_hash
+    }
+}
+
 impl Client<'_> {
     pub fn new(transactions_api: &str) -> Client {
         Client {
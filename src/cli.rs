@@ -0,0 +1,25 @@
+//! Command-line argument parsing.
+use structopt::StructOpt;
+
+use crate::exit::ExitCode;
+use crate::version;
+
+/// Parse the command-line arguments of a tool, exiting with a usage error if they are invalid.
+///
+/// The long version shown by `--version` includes the given details, see [`version::long`].
+pub fn parse_args<T: StructOpt>(version: &str, details: &[(&str, String)]) -> T {
+    let version = version::long(version, details);
+    let matches = T::clap()
+        .long_version(version.as_str())
+        .get_matches_safe()
+        .unwrap_or_else(|err| {
+            if err.use_stderr() {
+                eprintln!("{}", err.message);
+                ExitCode::BadInput.exit()
+            } else {
+                err.exit()
+            }
+        });
+
+    T::from_clap(&matches)
+}
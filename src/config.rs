@@ -0,0 +1,353 @@
+//! Configuration file.
+//!
+//! The configuration file holds persistent defaults for the options of the tools. Options
+//! given on the command line take precedence over environment variables, which take precedence
+//! over the configuration file.
+//!
+//! Named profiles, eg. `[profile.testnet]`, hold sets of defaults that are selected with
+//! `--profile`, and override the top-level defaults.
+//!
+//! Chain settings, eg. `[chain.rinkeby]`, apply whenever the Ethereum node is on that chain.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::{env, fs, io};
+
+use anyhow::{bail, Context as _};
+use coins_bip32::path::DerivationPath;
+use serde::Deserialize;
+
+use crate::output;
+
+/// Path of the configuration file, relative to the user's configuration directory.
+const CONFIG_PATH: &str = "radicle-client-tools/config.toml";
+
+/// Configuration error.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The configuration file could not be read.
+    #[error("failed to read configuration file {}: {err}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        err: io::Error,
+    },
+    /// The configuration file is not valid.
+    #[error("invalid configuration file {}: {err}", path.display())]
+    Toml {
+        path: PathBuf,
+        #[source]
+        err: toml::de::Error,
+    },
+    /// The selected profile is not defined in the configuration file.
+    #[error("profile '{name}' is not defined in the configuration file")]
+    UnknownProfile { name: String },
+}
+
+/// Persistent option defaults.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// JSON-RPC URL of Ethereum node (eg. http://localhost:8545).
+    pub rpc_url: Option<String>,
+    /// Keystore file containing encrypted private key.
+    pub keystore: Option<PathBuf>,
+    /// Account derivation path when using a Ledger hardware wallet.
+    pub ledger_hdpath: Option<String>,
+    /// Radicle org to operate on.
+    pub org: Option<String>,
+    /// Link template of a project commit in a web frontend, see [`crate::explorer::Frontend`].
+    pub commit_url: Option<String>,
+    /// Settings per chain, by chain name, eg. `rinkeby`.
+    #[serde(default, rename = "chain")]
+    pub chains: HashMap<String, Chain>,
+    /// Named profiles, overriding the top-level defaults when selected.
+    #[serde(default, rename = "profile")]
+    pub profiles: HashMap<String, Config>,
+    /// Command aliases of `rad-tools`, eg. `release = "anchor --profile mainnet --yes"`.
+    #[serde(default, rename = "alias")]
+    pub aliases: HashMap<String, String>,
+}
+
+/// Settings of a chain.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Chain {
+    /// Block explorer URL to link transactions to (eg. `https://rinkeby.etherscan.io`).
+    pub explorer_url: Option<String>,
+    /// Faucet endpoint to request funds from when an account can't pay for gas, on testnets
+    /// only. The address of the account is posted to it as JSON, eg. `{"address":"0x1a2b.."}`.
+    pub faucet_url: Option<String>,
+    /// Block to start looking for anchors from, eg. the block the org was created in.
+    pub from_block: Option<u64>,
+}
+
+impl Config {
+    /// Load the user's configuration file, if there is one.
+    pub fn load() -> Result<Self, Error> {
+        match path() {
+            Some(path) if path.exists() => Self::read(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load the user's configuration file, with the given profile selected.
+    ///
+    /// When no profile is given, the profile named by `RAD_PROFILE` is selected, if set.
+    pub fn load_profile(name: Option<&str>) -> Result<Self, Error> {
+        let config = Self::load()?;
+
+        match name.map(str::to_owned).or_else(|| env_var("RAD_PROFILE")) {
+            Some(name) => config.select(&name),
+            None => Ok(config),
+        }
+    }
+
+    /// Select a profile, overriding the top-level defaults with the ones of the profile.
+    pub fn select(mut self, name: &str) -> Result<Self, Error> {
+        let profile = self
+            .profiles
+            .remove(name)
+            .ok_or_else(|| Error::UnknownProfile {
+                name: name.to_owned(),
+            })?;
+
+        let mut chains = self.chains;
+        chains.extend(profile.chains);
+
+        Ok(Self {
+            rpc_url: profile.rpc_url.or(self.rpc_url),
+            keystore: profile.keystore.or(self.keystore),
+            ledger_hdpath: profile.ledger_hdpath.or(self.ledger_hdpath),
+            org: profile.org.or(self.org),
+            commit_url: profile.commit_url.or(self.commit_url),
+            chains,
+            profiles: HashMap::new(),
+            aliases: self.aliases,
+        })
+    }
+
+    /// Get the command and arguments an alias expands to.
+    ///
+    /// Arguments are separated by whitespace, and can't contain whitespace themselves.
+    pub fn alias(&self, name: &str) -> Option<Vec<&str>> {
+        self.aliases
+            .get(name)
+            .map(|expansion| expansion.split_whitespace().collect())
+            .filter(|args: &Vec<&str>| !args.is_empty())
+    }
+
+    /// Get the JSON-RPC URL of the Ethereum node, given with `--rpc-url`, `ETH_RPC_URL` or in
+    /// this configuration, in that order.
+    ///
+    /// When running in a terminal, the URL is asked for if it isn't given otherwise.
+    pub fn resolve_rpc_url(&self, rpc_url: Option<String>) -> anyhow::Result<String> {
+        match rpc_url
+            .or_else(|| env_var("ETH_RPC_URL"))
+            .or_else(|| self.rpc_url.clone())
+        {
+            Some(url) => Ok(url),
+            None if output::interactive() => {
+                Ok(output::input("Ethereum JSON-RPC URL", String::from_str)?)
+            }
+            None => bail!("an Ethereum JSON-RPC URL must be specified with '--rpc-url'"),
+        }
+    }
+
+    /// Get the wallet to sign with, as a keystore file and a Ledger derivation path, given
+    /// with `--keystore` and `--ledger-hdpath`, `RAD_KEYSTORE` and `ETH_HDPATH`, or in this
+    /// configuration, in that order.
+    pub fn resolve_wallet(
+        &self,
+        keystore: Option<PathBuf>,
+        ledger_hdpath: Option<DerivationPath>,
+    ) -> anyhow::Result<(Option<PathBuf>, Option<DerivationPath>)> {
+        let keystore = keystore.or_else(|| env_var("RAD_KEYSTORE").map(PathBuf::from));
+        let ledger_hdpath = match ledger_hdpath {
+            Some(path) => Some(path),
+            None => env_var("ETH_HDPATH")
+                .map(|path| DerivationPath::from_str(&path))
+                .transpose()
+                .context("invalid value specified for 'ETH_HDPATH'")?,
+        };
+
+        // The configured wallet is only used if no wallet was specified otherwise, so that a
+        // wallet given for one run doesn't end up combined with the configured one.
+        if keystore.is_some() || ledger_hdpath.is_some() {
+            return Ok((keystore, ledger_hdpath));
+        }
+        let ledger_hdpath = self
+            .ledger_hdpath
+            .as_deref()
+            .map(DerivationPath::from_str)
+            .transpose()
+            .context("invalid value specified for 'ledger-hdpath' in configuration")?;
+
+        Ok((self.keystore.clone(), ledger_hdpath))
+    }
+
+    /// Read a configuration file.
+    pub fn read(path: PathBuf) -> Result<Self, Error> {
+        let contents = fs::read_to_string(&path).map_err(|err| Error::Io {
+            path: path.clone(),
+            err,
+        })?;
+
+        toml::from_str(&contents).map_err(|err| Error::Toml { path, err })
+    }
+}
+
+/// Get an environment variable used as an option fallback, ignoring empty values.
+pub fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Get the path of the user's configuration file.
+///
+/// This is `$XDG_CONFIG_HOME/radicle-client-tools/config.toml`, falling back to
+/// `~/.config/radicle-client-tools/config.toml`.
+pub fn path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(CONFIG_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+            rpc-url = "http://localhost:8545"
+            ledger-hdpath = "m/44'/60'/0'/0/0"
+            org = "0x2f94AeA3f1760ECC7A38876A9662986385A80552"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.rpc_url.as_deref(), Some("http://localhost:8545"));
+        assert_eq!(config.ledger_hdpath.as_deref(), Some("m/44'/60'/0'/0/0"));
+        assert!(config.keystore.is_none());
+    }
+
+    #[test]
+    fn test_parse_empty_config() {
+        let config: Config = toml::from_str("").unwrap();
+
+        assert!(config.rpc_url.is_none());
+        assert!(config.org.is_none());
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let config: Config = toml::from_str(
+            r#"
+            rpc-url = "http://localhost:8545"
+            org = "0x2f94AeA3f1760ECC7A38876A9662986385A80552"
+
+            [profile.testnet]
+            rpc-url = "https://rinkeby.infura.io/v3/0000"
+            keystore = "/home/alice/testnet.json"
+            "#,
+        )
+        .unwrap();
+
+        let testnet = config.clone().select("testnet").unwrap();
+        assert_eq!(
+            testnet.rpc_url.as_deref(),
+            Some("https://rinkeby.infura.io/v3/0000")
+        );
+        assert_eq!(
+            testnet.keystore,
+            Some(PathBuf::from("/home/alice/testnet.json"))
+        );
+        assert_eq!(testnet.org, config.org);
+
+        assert!(matches!(
+            config.select("devnet"),
+            Err(Error::UnknownProfile { name }) if name == "devnet"
+        ));
+    }
+
+    #[test]
+    fn test_chains() {
+        let config: Config = toml::from_str(
+            r#"
+            [chain.rinkeby]
+            explorer-url = "https://rinkeby.etherscan.io"
+            faucet-url = "https://faucet.rinkeby.example"
+            from-block = 8000000
+
+            [profile.testnet.chain.rinkeby]
+            explorer-url = "https://blockscout.com/eth/rinkeby"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.chains["rinkeby"].explorer_url.as_deref(),
+            Some("https://rinkeby.etherscan.io")
+        );
+        assert_eq!(
+            config.chains["rinkeby"].faucet_url.as_deref(),
+            Some("https://faucet.rinkeby.example")
+        );
+        assert_eq!(config.chains["rinkeby"].from_block, Some(8000000));
+        assert!(!config.chains.contains_key("homestead"));
+
+        let testnet = config.select("testnet").unwrap();
+        assert_eq!(
+            testnet.chains["rinkeby"].explorer_url.as_deref(),
+            Some("https://blockscout.com/eth/rinkeby")
+        );
+    }
+
+    #[test]
+    fn test_resolve_wallet() {
+        let config: Config = toml::from_str(
+            r#"
+            keystore = "/var/keystore"
+            ledger-hdpath = "m/44'/60'/0'/x"
+            "#,
+        )
+        .unwrap();
+
+        // A wallet given on the command-line is used instead of the configured one.
+        let (keystore, ledger_hdpath) = config
+            .resolve_wallet(Some(PathBuf::from("keystore")), None)
+            .unwrap();
+        assert_eq!(keystore, Some(PathBuf::from("keystore")));
+        assert!(ledger_hdpath.is_none());
+
+        let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+        let (keystore, ledger_hdpath) = config.resolve_wallet(None, Some(path.clone())).unwrap();
+        assert!(keystore.is_none());
+        assert_eq!(ledger_hdpath, Some(path));
+
+        // An invalid configured derivation path is an error, not a missing wallet.
+        assert!(config.resolve_wallet(None, None).is_err());
+    }
+
+    #[test]
+    fn test_alias() {
+        let config: Config = toml::from_str(
+            r#"
+            [alias]
+            release = "anchor --profile mainnet  --yes"
+            empty = ""
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.alias("release"),
+            Some(vec!["anchor", "--profile", "mainnet", "--yes"])
+        );
+        assert_eq!(config.alias("empty"), None);
+        assert_eq!(config.alias("anchor"), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Dry runs and transaction reviews.
+//!
+//! A dry run of a command goes as far as it can without signing, sending or writing anything,
+//! and prints the plan of what the command would have done instead. The plan is a JSON object,
+//! which is printed as-is with `--json`, and as an indented summary otherwise.
+//!
+//! The same plan is shown for review before a transaction is signed.
+use std::io::{self, Write};
+
+use serde_json::Value;
+
+use crate::output;
+
+/// Print the plan of a dry run to stdout.
+pub fn print(plan: &Value, json: bool) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    if json {
+        serde_json::to_writer_pretty(&mut stdout, plan)?;
+        writeln!(stdout)?;
+    } else {
+        write_summary(&mut stdout, plan, None, 0)?;
+    }
+    log::info!("Dry run, nothing was signed or sent");
+
+    Ok(())
+}
+
+/// Print the plan of a transaction to stderr for review, before asking for confirmation.
+///
+/// Values that differ from the given defaults, eg. the configured org, are highlighted. The defaults
+/// have the same shape as the plan, but only need to hold the values that have a default.
+pub fn review(plan: &Value, defaults: &Value) -> io::Result<()> {
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+
+    writeln!(stderr, "{}", output::highlight("Transaction"))?;
+    write_summary(&mut stderr, plan, Some(defaults), 2)
+}
+
+/// Write a plan as an indented summary, one value per line.
+fn write_summary(
+    w: &mut impl Write,
+    value: &Value,
+    defaults: Option<&Value>,
+    indent: usize,
+) -> io::Result<()> {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let default = defaults.and_then(|defaults| defaults.get(name));
+
+                if value.is_object() || value.is_array() {
+                    writeln!(w, "{:indent$}{}:", "", name, indent = indent)?;
+                    write_summary(w, value, default, indent + 2)?;
+                } else if let Some(default) = default.filter(|default| *default != value) {
+                    writeln!(
+                        w,
+                        "{:indent$}{}: {} {}",
+                        "",
+                        name,
+                        output::highlight(&scalar(value)),
+                        output::dim(&format!("(default: {})", scalar(default))),
+                        indent = indent
+                    )?;
+                } else {
+                    writeln!(
+                        w,
+                        "{:indent$}{}: {}",
+                        "",
+                        name,
+                        scalar(value),
+                        indent = indent
+                    )?;
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                if value.is_object() || value.is_array() {
+                    writeln!(w, "{:indent$}-", "", indent = indent)?;
+                    write_summary(w, value, None, indent + 2)?;
+                } else {
+                    writeln!(w, "{:indent$}- {}", "", scalar(value), indent = indent)?;
+                }
+            }
+        }
+        value => writeln!(w, "{:indent$}{}", "", scalar(value), indent = indent)?,
+    }
+    Ok(())
+}
+
+/// Format a scalar value, without quoting strings.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_write_summary() {
+        let plan = json!({
+            "chain": { "id": 4, "name": "Rinkeby" },
+            "method": "anchor",
+            "owners": ["0x1a", "0x2b"],
+            "safe": null,
+        });
+        let mut out = Vec::new();
+
+        write_summary(&mut out, &plan, None, 0).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "chain:\n  id: 4\n  name: Rinkeby\nmethod: anchor\nowners:\n  - 0x1a\n  - 0x2b\nsafe: null\n"
+        );
+    }
+
+    #[test]
+    fn test_write_summary_defaults() {
+        colored::control::set_override(false);
+
+        let plan = json!({
+            "contract": "0x2f94",
+            "args": { "owner": "0x1a", "threshold": 3 },
+        });
+        let defaults = json!({
+            "contract": "0x9a1c",
+            "args": { "threshold": 2 },
+        });
+        let mut out = Vec::new();
+
+        write_summary(&mut out, &plan, Some(&defaults), 0).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "args:\n  owner: 0x1a\n  threshold: 3 (default: 2)\ncontract: 0x2f94 (default: 0x9a1c)\n"
+        );
+    }
+}
@@ -0,0 +1,475 @@
+//! Ethereum networks, wallets and transactions.
+//!
+//! Shared by the tools that sign and send transactions, eg. `rad-anchor` and `rad-org`.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+
+use coins_bip32::path::DerivationPath;
+
+use ethers::{
+    abi::Detokenize,
+    prelude::{builders::ContractCall, Bytes, JsonRpcClient, Signer, U256},
+    providers::{Http, Provider, ProviderError},
+    signers::{HDPath, Ledger, LedgerError, LocalWallet},
+    types::{Address, TransactionReceipt, H256},
+};
+
+use ethers::prelude::Middleware;
+
+use serde_json::json;
+
+use safe_transaction_client as safe;
+
+use crate::config;
+use crate::exit::ExitCode;
+use crate::explorer::Explorer;
+use crate::interrupt;
+use crate::output;
+
+/// Ethereum network.
+#[derive(Debug)]
+pub enum Network {
+    Homestead,
+    Rinkeby,
+}
+
+impl Network {
+    /// All supported networks.
+    pub const ALL: [Self; 2] = [Self::Homestead, Self::Rinkeby];
+
+    /// Get the name of the network, as used in the configuration file, eg. `rinkeby`.
+    pub fn name(&self) -> String {
+        format!("{:?}", self).to_lowercase()
+    }
+
+    /// Get the settings of the network in the configuration file.
+    pub fn config(&self, chains: &HashMap<String, config::Chain>) -> config::Chain {
+        chains.get(&self.name()).cloned().unwrap_or_default()
+    }
+
+    /// Get the block explorer of the network, unless another one is given.
+    pub fn explorer(&self, url: Option<String>, config: &config::Chain, open: bool) -> Explorer {
+        let url = url
+            .or_else(|| config.explorer_url.clone())
+            .unwrap_or_else(|| self.explorer_url().to_owned());
+
+        Explorer { url, open }
+    }
+
+    /// Whether this is a testnet, where funds are free and can be requested from a faucet.
+    pub const fn is_testnet(&self) -> bool {
+        match self {
+            Self::Homestead => false,
+            Self::Rinkeby => true,
+        }
+    }
+
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Self::Homestead => 1,
+            Self::Rinkeby => 4,
+        }
+    }
+
+    pub const fn safe_transaction_url(&self) -> &'static str {
+        match self {
+            Self::Homestead => "https://safe-transaction.gnosis.io",
+            Self::Rinkeby => "https://safe-transaction.rinkeby.gnosis.io",
+        }
+    }
+
+    pub const fn explorer_url(&self) -> &'static str {
+        match self {
+            Self::Homestead => "https://etherscan.io",
+            Self::Rinkeby => "https://rinkeby.etherscan.io",
+        }
+    }
+
+    /// Get the chain id and name, as shown in execution plans.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({ "id": self.chain_id(), "name": format!("{:?}", self) })
+    }
+}
+
+/// Get the supported chains, along with their chain id, eg. `homestead (1)`.
+pub fn chains() -> String {
+    Network::ALL
+        .iter()
+        .map(|network| format!("{} ({})", network.name(), network.chain_id()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl TryFrom<u64> for Network {
+    type Error = ();
+
+    fn try_from(other: u64) -> Result<Self, ()> {
+        match other {
+            1 => Ok(Self::Homestead),
+            4 => Ok(Self::Rinkeby),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Ethereum error.
+#[derive(thiserror::Error, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum Error {
+    /// No wallet specified.
+    #[error("no wallet specified")]
+    NoWallet,
+    /// The keystore password can't be asked for.
+    #[error("keystore password required but stdin is not a terminal")]
+    NoTerminal,
+    /// The keystore password could not be read.
+    #[error("reading the keystore password failed")]
+    Password(#[source] std::io::Error),
+    /// The keystore could not be decrypted.
+    #[error("keystore decryption failed")]
+    KeystoreDecryption,
+    /// The Ethereum node is on a chain that isn't supported.
+    #[error("unsupported chain id '{0}'")]
+    UnsupportedChain(u64),
+    /// A request to the Ethereum node failed.
+    #[error("JSON-RPC request failed")]
+    Rpc(#[source] ProviderError),
+    /// The transaction was not confirmed by the user.
+    #[error("transaction aborted")]
+    Aborted,
+    /// The account has no funds to pay for gas.
+    #[error("account {0:?} has no funds to pay for gas")]
+    NoFunds(Address),
+    /// Testnet funds were requested from a faucet, and have yet to arrive.
+    #[error("testnet funds were requested for account {0:?}, try again once they have arrived")]
+    FundsRequested(Address),
+    /// The transaction can't be confirmed, as the user can't be asked.
+    #[error("confirmation required but stdin is not a terminal")]
+    NoConfirmation,
+    /// The transaction was reverted by the contract.
+    #[error("transaction reverted: {0}")]
+    Reverted(String),
+    /// Gnosis Safe error.
+    #[error("safe transaction error: {0}")]
+    Safe(#[from] safe::Error),
+    /// Signature error.
+    #[error("signer error: {0}")]
+    Signer(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Get a hint on how to resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NoWallet => Some(
+                "specify a wallet with '--ledger-hdpath' or '--keystore', \
+                or set 'ETH_HDPATH' or 'RAD_KEYSTORE'",
+            ),
+            Self::NoConfirmation => {
+                Some("use '--yes' to submit the transaction without confirmation")
+            }
+            Self::NoTerminal => Some(
+                "run in a terminal to enter the keystore password, \
+                or use a Ledger with '--ledger-hdpath' or 'ETH_HDPATH'",
+            ),
+            Self::KeystoreDecryption => Some(
+                "check the keystore password, and that '--keystore' or 'RAD_KEYSTORE' \
+                points to the right file",
+            ),
+            Self::UnsupportedChain(_) => Some(
+                "only mainnet and rinkeby are supported, \
+                check which chain '--rpc-url' or 'ETH_RPC_URL' points to",
+            ),
+            Self::Rpc(_) => {
+                Some("check that '--rpc-url' or 'ETH_RPC_URL' points to a reachable Ethereum node")
+            }
+            Self::NoFunds(_) => Some(
+                "send funds to the account, or on a testnet, configure a faucet to request \
+                them from with 'faucet-url' under '[chain.<name>]'",
+            ),
+            Self::Password(_)
+            | Self::FundsRequested(_)
+            | Self::Aborted
+            | Self::Reverted(_)
+            | Self::Safe(_)
+            | Self::Signer(_) => None,
+        }
+    }
+
+    /// Get the exit code of a tool failing with this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::NoWallet
+            | Self::NoTerminal
+            | Self::NoConfirmation
+            | Self::UnsupportedChain(_) => ExitCode::BadInput,
+            Self::Password(_) | Self::KeystoreDecryption | Self::Signer(_) => ExitCode::AuthFailure,
+            Self::Rpc(_) => ExitCode::RpcFailure,
+            Self::Aborted => ExitCode::UserAbort,
+            Self::Reverted(_) => ExitCode::ContractRevert,
+            Self::NoFunds(_) | Self::FundsRequested(_) | Self::Safe(_) => ExitCode::Failure,
+        }
+    }
+}
+
+/// Get the exit code of a tool failing with the given error.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    if let Some(err) = err.downcast_ref::<Error>() {
+        err.exit_code()
+    } else if err.is::<ProviderError>() {
+        ExitCode::RpcFailure
+    } else if err.is::<LedgerError>() {
+        ExitCode::AuthFailure
+    } else {
+        ExitCode::Failure
+    }
+}
+
+/// Get a hint on how to resolve the given error, if there is one.
+pub fn hint(err: &anyhow::Error) -> Option<&'static str> {
+    err.downcast_ref::<Error>().and_then(Error::hint)
+}
+
+/// Ask the user to confirm submitting a transaction, unless it was confirmed up-front.
+pub fn confirm(yes: bool) -> anyhow::Result<()> {
+    match output::confirm("Submit this transaction?", yes) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Error::Aborted.into()),
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidInput => {
+            Err(Error::NoConfirmation.into())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Convert the error of a contract call, telling reverts apart from other failures.
+pub fn call_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> anyhow::Error {
+    let msg = err.to_string();
+
+    if msg.contains("execution reverted") {
+        Error::Reverted(msg).into()
+    } else {
+        err.into()
+    }
+}
+
+/// Wallet used to sign transactions and messages.
+pub enum Wallet {
+    /// Private key decrypted from a keystore file.
+    Keystore(LocalWallet),
+    /// Ledger hardware wallet.
+    Ledger(Ledger),
+}
+
+/// Get a provider for the Ethereum node at the given JSON-RPC URL.
+pub fn provider(rpc_url: &str) -> anyhow::Result<Provider<Http>> {
+    Provider::<Http>::try_from(rpc_url).context("JSON-RPC URL parsing failed")
+}
+
+/// Connect to an Ethereum node, returning the provider along with the chain id of the node.
+pub async fn connect(rpc_url: &str) -> anyhow::Result<(Provider<Http>, u64)> {
+    let provider = provider(rpc_url)?;
+    let chain_id = provider.get_chainid().await.map_err(Error::Rpc)?.as_u64();
+
+    Ok((provider, chain_id))
+}
+
+/// Open the wallet given by a keystore file or, if there is none, a Ledger derivation path.
+///
+/// The password of a keystore is asked for on the terminal.
+pub async fn wallet(
+    keystore: Option<&Path>,
+    ledger_hdpath: Option<&DerivationPath>,
+    chain_id: u64,
+) -> anyhow::Result<Wallet> {
+    if let Some(keypath) = keystore {
+        log::info!("Decrypting keystore..");
+
+        let password = output::password("Password").map_err(|err| match err.kind() {
+            std::io::ErrorKind::InvalidInput => Error::NoTerminal,
+            _ => Error::Password(err),
+        })?;
+        let signer = LocalWallet::decrypt_keystore(keypath, password)
+            .map_err(|_| Error::KeystoreDecryption)?
+            .with_chain_id(chain_id);
+
+        log::debug!("Keystore decrypted: {:?}.", signer);
+
+        Ok(Wallet::Keystore(signer))
+    } else if let Some(path) = ledger_hdpath {
+        let hdpath = path.derivation_string();
+        let spinner = output::spinner("Connecting to Ledger..");
+        let signer = Ledger::new(HDPath::Other(hdpath), chain_id).await?;
+        spinner.finish();
+
+        Ok(Wallet::Ledger(signer))
+    } else {
+        Err(Error::NoWallet.into())
+    }
+}
+
+/// Get the Safe at the given address, or nothing if the address isn't a Safe.
+pub fn find_safe<'a>(
+    client: &'a safe::Client<'_>,
+    address: Address,
+) -> anyhow::Result<Option<safe::Safe<'a>>> {
+    match client.get_safe(address) {
+        Ok(safe) => Ok(Some(safe)),
+        Err(err) if err.is_not_found() => Ok(None),
+        Err(err) => {
+            bail!("request to safe transaction API failed: {:?}", err);
+        }
+    }
+}
+
+/// Build the execution plan of a transaction, without signing or sending it.
+#[allow(clippy::too_many_arguments)]
+pub async fn plan<P: JsonRpcClient + 'static>(
+    provider: &Provider<P>,
+    to: Address,
+    method: &str,
+    call: ContractCall<Provider<P>, ()>,
+    args: serde_json::Value,
+    signer: Address,
+    network: &Network,
+    safe: Option<(Address, &safe::Safe<'_>)>,
+) -> anyhow::Result<serde_json::Value> {
+    let mut plan = json!({
+        "chain": network.to_json(),
+        "contract": to,
+        "method": method,
+        "args": args,
+        "data": call.calldata(),
+        "signer": signer,
+    });
+
+    if let Some((address, safe)) = safe {
+        plan["safe"] = json!({
+            "address": address,
+            "nonce": safe.nonce.to_string(),
+            "threshold": safe.threshold,
+        });
+    } else {
+        let nonce = provider.get_transaction_count(signer, None).await?;
+        let gas = call.estimate_gas().await.map_err(call_error)?;
+        let gas_price = provider.get_gas_price().await?;
+
+        plan["nonce"] = json!(nonce.to_string());
+        plan["fee"] = json!({
+            "gas": gas.to_string(),
+            "gasPrice": gas_price.to_string(),
+            "total": (gas * gas_price).to_string(),
+        });
+    }
+    Ok(plan)
+}
+
+/// Sign a transaction and propose it to a Safe, returning the hash identifying it in the Safe.
+pub async fn propose<S: Signer + 'static>(
+    to: Address,
+    data: Bytes,
+    safe: &safe::Safe<'_>,
+    signer: &S,
+) -> anyhow::Result<H256> {
+    let safe_tx = safe.create_transaction(to, U256::zero(), data, safe::Operation::Call);
+    let spinner = output::spinner("Signing transaction..");
+    let signed_tx = safe_tx
+        .sign(signer)
+        .await
+        .map_err(|err| Error::Signer(Box::new(err)))?;
+    let safe_tx_hash = signed_tx.safe_tx_hash();
+    spinner.finish();
+
+    let spinner = output::spinner("Proposing transaction..");
+    safe.propose(signed_tx)?;
+    spinner.finish();
+
+    log::info!("Transaction proposed to Safe, awaiting confirmation by its owners");
+
+    Ok(safe_tx_hash)
+}
+
+/// Send a transaction and wait for it to be included, sending it again if it is dropped.
+pub async fn send<M: Middleware + 'static, D: Detokenize>(
+    call: ContractCall<M, D>,
+    explorer: &Explorer,
+) -> anyhow::Result<TransactionReceipt> {
+    let (receipt, url) = loop {
+        let spinner = output::spinner("Sending transaction..");
+        let pending = call.send().await.map_err(call_error)?;
+        let tx_hash = *pending;
+        spinner.finish();
+
+        let url = explorer.transaction_url(&format!("{:?}", tx_hash));
+
+        log::info!(
+            "Waiting for transaction {} to be included..",
+            output::link(&format!("{:?}", tx_hash), &url)
+        );
+        log::info!("View transaction at {}", url);
+        interrupt::sent(&format!("{:?}", tx_hash), &url);
+
+        let spinner = output::spinner("Waiting for confirmation..");
+        let result = pending.await?;
+        spinner.finish();
+        interrupt::settled();
+
+        if let Some(result) = result {
+            break (result, url);
+        }
+        log::info!("Transaction {} dropped, retrying..", tx_hash);
+    };
+
+    // Only the transaction that was included is opened, not the ones that were dropped.
+    if let Err(err) = explorer.open(&url) {
+        log::warn!("Warning: failed to open {}: {}", url, err);
+    }
+    Ok(receipt)
+}
+
+/// Make sure an account can pay for gas.
+///
+/// On a testnet with a faucet configured, the user is offered to request funds from it.
+pub async fn check_balance<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    address: Address,
+    network: &Network,
+    faucet: Option<&str>,
+) -> anyhow::Result<()> {
+    let balance = provider.get_balance(address, None).await?;
+
+    if !balance.is_zero() {
+        return Ok(());
+    }
+    match faucet {
+        Some(faucet) if network.is_testnet() => {
+            let msg = format!(
+                "Account has no funds. Request testnet funds from {}?",
+                faucet
+            );
+
+            // Without a terminal, the user can't be asked, which is the same as declining.
+            if !output::confirm(&msg, false).unwrap_or(false) {
+                return Err(Error::NoFunds(address).into());
+            }
+            request_funds(faucet, address)?;
+            log::info!("Requested testnet funds from {}", faucet);
+
+            Err(Error::FundsRequested(address).into())
+        }
+        _ => Err(Error::NoFunds(address).into()),
+    }
+}
+
+/// Request testnet funds for an account from a faucet, which is sent the address of the
+/// account as JSON, eg. `{"address":"0x1a2b.."}`.
+pub fn request_funds(faucet: &str, address: Address) -> anyhow::Result<()> {
+    ureq::post(faucet)
+        .send_json(json!({ "address": address }))
+        .with_context(|| format!("request to faucet {} failed", faucet))?;
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Process exit codes.
+//!
+//! The exit codes are shared by all tools, so that scripts can tell failures apart without
+//! parsing error messages. Existing codes must not be renumbered.
+use std::process;
+
+use crate::output;
+
+/// Exit code of a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The command succeeded.
+    Success = 0,
+    /// The command failed for a reason not covered by the other codes.
+    Failure = 1,
+    /// The options, environment or configuration are invalid.
+    BadInput = 2,
+    /// The user aborted the command, eg. by not confirming a transaction, or with Ctrl-C.
+    UserAbort = 3,
+    /// The wallet could not be unlocked, or failed to sign.
+    AuthFailure = 4,
+    /// A request to the Ethereum node failed.
+    RpcFailure = 5,
+    /// The transaction was reverted by the contract.
+    ContractRevert = 6,
+    /// There was nothing to do, eg. the commit is already anchored.
+    AlreadyDone = 7,
+}
+
+impl ExitCode {
+    /// Exit the process with this code.
+    pub fn exit(self) -> ! {
+        process::exit(self.into())
+    }
+
+    /// Report the error a tool failed with, along with a hint on how to resolve it, if any,
+    /// and exit the process with this code.
+    pub fn fail(self, err: &anyhow::Error, hint: Option<&str>) -> ! {
+        if let Some(cause) = err.source() {
+            log::error!("Error: {} ({})", err, cause);
+        } else {
+            log::error!("Error: {}", err);
+        }
+        if let Some(hint) = hint {
+            eprintln!("{}", output::hint(hint));
+        }
+        self.exit()
+    }
+}
+
+impl From<ExitCode> for i32 {
+    fn from(code: ExitCode) -> Self {
+        code as i32
+    }
+}
@@ -0,0 +1,87 @@
+//! Block explorer and web frontend links.
+use std::io;
+use std::process::Command;
+
+/// Block explorer used to link to transactions.
+#[derive(Debug, Clone)]
+pub struct Explorer {
+    /// Base URL of the block explorer (eg. https://etherscan.io).
+    pub url: String,
+    /// Whether to open links in the browser.
+    pub open: bool,
+}
+
+impl Explorer {
+    /// Get the link to a transaction, given its hash.
+    pub fn transaction_url(&self, tx_hash: &str) -> String {
+        format!("{}/tx/{}", self.url.trim_end_matches('/'), tx_hash)
+    }
+
+    /// Open a link in the browser, if opening links was requested.
+    pub fn open(&self, url: &str) -> io::Result<()> {
+        if self.open {
+            open(url)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Web frontend used to link to project commits.
+#[derive(Debug, Clone)]
+pub struct Frontend {
+    /// Link template of a commit, where `{project}` and `{commit}` are replaced by the project
+    /// URN and commit hash, eg. `https://app.radicle.network/{project}/commits/{commit}`.
+    pub commit_url: String,
+}
+
+impl Frontend {
+    /// Get the link to a project commit.
+    pub fn commit_url(&self, project: &str, commit: &str) -> String {
+        self.commit_url
+            .replace("{project}", project)
+            .replace("{commit}", commit)
+    }
+}
+
+/// Open a URL in the default browser.
+pub fn open(url: &str) -> io::Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(url).spawn()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_url() {
+        let explorer = Explorer {
+            url: "https://rinkeby.etherscan.io/".to_owned(),
+            open: false,
+        };
+
+        assert_eq!(
+            explorer.transaction_url("0x1a2b"),
+            "https://rinkeby.etherscan.io/tx/0x1a2b"
+        );
+    }
+
+    #[test]
+    fn test_commit_url() {
+        let frontend = Frontend {
+            commit_url: "https://app.radicle.network/{project}/commits/{commit}".to_owned(),
+        };
+
+        assert_eq!(
+            frontend.commit_url("rad:git:hnrkemob", "ee61b4ff"),
+            "https://app.radicle.network/rad:git:hnrkemob/commits/ee61b4ff"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Interrupt handling.
+//!
+//! By default, Ctrl-C kills a tool on the spot, which can leave the terminal without echo
+//! during a password prompt, or without a cursor while a spinner is shown, and says nothing
+//! about a transaction that was already sent. The handler installed here restores the terminal,
+//! reports the pending transaction, if any, and exits with [`ExitCode::UserAbort`].
+//!
+//! Like git, interrupts are ignored while a pager is running, as the pager handles Ctrl-C
+//! itself. Exiting would leave the pager behind, fighting the shell over the terminal.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::exit::ExitCode;
+use crate::output;
+
+lazy_static::lazy_static! {
+    /// Transaction that was sent, but not yet included, along with its block explorer link.
+    static ref PENDING: Mutex<Option<(String, String)>> = Mutex::new(None);
+}
+
+/// Whether a pager is running.
+static PAGING: AtomicBool = AtomicBool::new(false);
+
+/// Install the interrupt handler. Should be called once, at startup.
+pub fn install() -> Result<(), ctrlc::Error> {
+    let terminal = term::save();
+
+    ctrlc::set_handler(move || {
+        if is_paging() {
+            return;
+        }
+        if let Some(terminal) = &terminal {
+            term::restore(terminal);
+        }
+        report(io::stderr()).ok();
+
+        ExitCode::UserAbort.exit();
+    })
+}
+
+/// Report the interrupt, along with the pending transaction, if any.
+fn report(mut out: impl Write) -> io::Result<()> {
+    // Move past a partially written line, eg. a prompt.
+    writeln!(out)?;
+    writeln!(out, "{}", output::negative("Interrupted"))?;
+
+    if let Some((tx_hash, url)) = PENDING.lock().ok().and_then(|pending| pending.clone()) {
+        writeln!(
+            out,
+            "Transaction {} was already sent, and may still be included.",
+            tx_hash
+        )?;
+        writeln!(out, "{}", output::hint(&format!("follow it at {}", url)))?;
+    }
+    Ok(())
+}
+
+/// Record that a transaction was sent, so that it is reported if the tool is interrupted
+/// before the transaction is included.
+pub fn sent(tx_hash: &str, url: &str) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some((tx_hash.to_owned(), url.to_owned()));
+    }
+}
+
+/// Record that the pending transaction was included, or dropped.
+pub fn settled() {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = None;
+    }
+}
+
+/// Record whether a pager is running, during which interrupts are ignored.
+pub fn paging(running: bool) {
+    PAGING.store(running, Ordering::SeqCst);
+}
+
+/// Whether a pager is running.
+pub(crate) fn is_paging() -> bool {
+    PAGING.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod term {
+    use std::io::{self, Write};
+    use std::mem;
+
+    /// Terminal settings of stdin, eg. whether input is echoed.
+    pub struct Terminal(libc::termios);
+
+    /// Save the terminal settings, if stdin is a terminal.
+    pub fn save() -> Option<Terminal> {
+        let mut termios = unsafe { mem::zeroed::<libc::termios>() };
+
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0 {
+            Some(Terminal(termios))
+        } else {
+            None
+        }
+    }
+
+    /// Restore the saved terminal settings, and show the cursor.
+    pub fn restore(terminal: &Terminal) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &terminal.0);
+        }
+        if atty::is(atty::Stream::Stderr) {
+            write!(io::stderr(), "\x1b[?25h").ok();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod term {
+    /// Terminal settings, which are left to the platform.
+    pub struct Terminal;
+
+    pub fn save() -> Option<Terminal> {
+        None
+    }
+
+    pub fn restore(_terminal: &Terminal) {}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    lazy_static::lazy_static! {
+        /// Held by tests using the global interrupt state, so that they don't run concurrently.
+        pub(crate) static ref STATE: Mutex<()> = Mutex::new(());
+    }
+
+    fn reported() -> String {
+        let mut out = Vec::new();
+        report(&mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_paging() {
+        let _state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+
+        paging(true);
+        assert!(is_paging());
+        paging(false);
+        assert!(!is_paging());
+    }
+
+    #[test]
+    fn test_report_pending() {
+        let _state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+        let tx_hash = "0x6e0a2bca4c1bbf2c6f46cd0e1f8a34ed0e3ab2a6c1d8b1f0e6b02c4de2f3f1aa";
+        let url = format!("https://etherscan.io/tx/{}", tx_hash);
+
+        assert!(reported().contains("Interrupted"));
+        assert!(!reported().contains("Transaction"));
+
+        sent(tx_hash, &url);
+        let report = reported();
+        assert!(report.contains(&format!("Transaction {} was already sent", tx_hash)));
+        assert!(report.contains(&url));
+
+        settled();
+        assert!(!reported().contains("Transaction"));
+    }
+}
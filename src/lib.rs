@@ -1 +1,11 @@
+pub mod cli;
+pub mod config;
+pub mod dry_run;
+pub mod ethereum;
+pub mod exit;
+pub mod explorer;
+pub mod interrupt;
 pub mod logger;
+pub mod output;
+pub mod pager;
+pub mod version;
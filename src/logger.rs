@@ -1,12 +1,69 @@
 //! Logging module.
+//!
+//! All records are written to stderr, so that stdout only carries the result of a command,
+//! eg. a listing or JSON, and can be piped.
+//!
+//! Records are formatted for humans by default, or as one JSON object per line for log
+//! collectors, see [`init_json`]. They can also be written to a log file, which is rotated
+//! to keep it bounded, see [`init_from_env`].
+//!
+//! The log level set with [`set_level`] can be overridden per module with a [`Filter`].
+//! Until it is set, eg. with [`set_verbosity`] once the options are parsed, it is
+//! [`INITIAL_LEVEL`].
 use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use colored::*;
-use log::{Level, Log, Metadata, Record, SetLoggerError};
+use log::kv;
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::config::env_var;
+
+mod file;
+mod filter;
+
+use file::LogFile;
+pub use filter::{Filter, FilterError};
+
+/// Log level of a newly initialized logger, so that problems with the options, environment or
+/// configuration are reported before the verbosity is known.
+pub const INITIAL_LEVEL: Level = Level::Warn;
+
+lazy_static::lazy_static! {
+    /// Log level and filter, set after the logger is installed.
+    static ref LEVELS: RwLock<Levels> = RwLock::new(Levels {
+        level: LevelFilter::Off,
+        filter: Filter::default(),
+    });
+}
+
+/// Log level set with [`set_level`], and filter set with [`set_filter`].
+struct Levels {
+    level: LevelFilter,
+    filter: Filter,
+}
+
+impl Levels {
+    /// Update the maximum level of the `log` crate, so that records enabled by either the
+    /// level or the filter reach the logger.
+    fn apply(&self) {
+        log::set_max_level(self.level.max(self.filter.max_level()));
+    }
+}
+
+/// Format of the log records.
+enum Format {
+    Text,
+    Json,
+}
 
 struct Logger {
     level: Level,
     target: &'static str,
+    format: Format,
+    file: Option<Mutex<LogFile>>,
 }
 
 impl Log for Logger {
@@ -17,17 +74,36 @@ impl Log for Logger {
     fn log(&self, record: &Record) {
         let metadata = record.metadata();
         let is_native = self.target == record.target();
+        let (level, filtered) = match LEVELS.read() {
+            Ok(levels) => (levels.level, levels.filter.level(record.target())),
+            Err(_) => (LevelFilter::max(), None),
+        };
 
-        // When using the "info" level, ignore all logs from other targets.
-        if metadata.level() == Level::Info && !is_native {
-            return;
-        }
+        let enabled = match filtered {
+            // Records matching a filter directive are only subject to the directive.
+            Some(filtered) => metadata.level() <= filtered,
+            None => {
+                // When using the "info" level, ignore all logs from other targets.
+                if metadata.level() == Level::Info && !is_native {
+                    return;
+                }
+                self.enabled(metadata) && metadata.level() <= level
+            }
+        };
 
-        if self.enabled(metadata) {
-            if record.level() == Level::Error {
-                write(record, record.target(), is_native, io::stderr());
-            } else {
-                write(record, record.target(), is_native, io::stdout());
+        if enabled {
+            match self.format {
+                Format::Text => write(record, record.target(), is_native, io::stderr()),
+                Format::Json => eprintln!("{}", json_line(record)),
+            }
+            if let Some(file) = &self.file {
+                let line = match self.format {
+                    Format::Text => text_line(record),
+                    Format::Json => json_line(record),
+                };
+                if let Ok(mut file) = file.lock() {
+                    file.write_line(&line).ok();
+                }
             }
 
             fn write(
@@ -65,17 +141,198 @@ impl Log for Logger {
     fn flush(&self) {}
 }
 
-/// Initialize a new logger.
+/// Format a record as a plain line of text, with a timestamp.
+fn text_line(record: &Record) -> String {
+    format!(
+        "{} {:<5} {}: {}",
+        timestamp(Utc::now()),
+        record.level(),
+        record.target(),
+        file::strip_ansi(&record.args().to_string())
+    )
+}
+
+/// Format a record as a JSON object on a single line.
+fn json_line(record: &Record) -> String {
+    let mut fields = Fields(serde_json::Map::new());
+    record.key_values().visit(&mut fields).ok();
+
+    serde_json::json!({
+        "timestamp": timestamp(Utc::now()),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": file::strip_ansi(&record.args().to_string()),
+        "fields": fields.0,
+    })
+    .to_string()
+}
+
+/// Structured fields of a record, eg. `log::info!(tx = tx_hash; "Sent")`.
+struct Fields(serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> kv::Visitor<'kvs> for Fields {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.insert(key.to_string(), value.to_string().into());
+
+        Ok(())
+    }
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with milliseconds, eg.
+/// `2021-06-17T09:30:00.000Z`.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Initialize a new logger, formatting records for humans.
 pub fn init(target: &'static str) -> Result<(), SetLoggerError> {
+    install(target, Format::Text, None)
+}
+
+/// Initialize a new logger, formatting each record as a JSON object on a single line, with the
+/// `timestamp`, `level`, `target`, `message` and structured `fields` of the record.
+pub fn init_json(target: &'static str) -> Result<(), SetLoggerError> {
+    install(target, Format::Json, None)
+}
+
+/// Initialize a new logger configured by the environment:
+///
+/// * `RAD_LOG_FORMAT`: the format of the records, either `text` (the default) or `json`.
+/// * `RAD_LOG_FILE`: a file to also write the records to, without colors.
+/// * `RAD_LOG_FILE_SIZE`: the size at which the log file is rotated, eg. `1M` (default: `10M`).
+/// * `RAD_LOG_FILE_KEEP`: the number of rotated log files to keep (default: `5`).
+/// * `RAD_LOG`: a [`Filter`] overriding the log level per module, eg. `ethers=debug`.
+pub fn init_from_env(target: &'static str) -> Result<(), SetLoggerError> {
+    let format = match env_var("RAD_LOG_FORMAT").as_deref() {
+        Some("json") => Format::Json,
+        Some("text") | None => Format::Text,
+        Some(other) => {
+            eprintln!(
+                "Warning: ignoring 'RAD_LOG_FORMAT': unknown format '{}', expected 'text' or 'json'",
+                other
+            );
+            Format::Text
+        }
+    };
+    let file = env_var("RAD_LOG_FILE").and_then(|path| {
+        let max_size = env_var("RAD_LOG_FILE_SIZE")
+            .and_then(|size| file::parse_size(&size))
+            .unwrap_or(file::DEFAULT_MAX_SIZE);
+        let keep = env_var("RAD_LOG_FILE_KEEP")
+            .and_then(|keep| keep.parse().ok())
+            .unwrap_or(file::DEFAULT_KEEP);
+
+        // Logging isn't set up yet, so failing to open the log file can only be reported
+        // directly.
+        match LogFile::open(PathBuf::from(&path), max_size, keep) {
+            Ok(file) => Some(file),
+            Err(err) => {
+                eprintln!("Warning: failed to open log file {}: {}", path, err);
+                None
+            }
+        }
+    });
+
+    install(target, format, file)?;
+
+    if let Some(filter) = env_var("RAD_LOG") {
+        match filter.parse() {
+            Ok(filter) => set_filter(filter),
+            Err(err) => eprintln!("Warning: ignoring 'RAD_LOG': {}", err),
+        }
+    }
+    Ok(())
+}
+
+fn install(
+    target: &'static str,
+    format: Format,
+    file: Option<LogFile>,
+) -> Result<(), SetLoggerError> {
     let level = log::Level::Debug;
-    let logger = Logger { level, target };
+    let logger = Logger {
+        level,
+        target,
+        format,
+        file: file.map(Mutex::new),
+    };
 
     log::set_boxed_logger(Box::new(logger))?;
+    set_level(INITIAL_LEVEL);
 
     Ok(())
 }
 
-/// Set the maximum log level.
+/// Set the maximum log level of records not matching the filter.
 pub fn set_level(level: log::Level) {
-    log::set_max_level(level.to_level_filter());
+    if let Ok(mut levels) = LEVELS.write() {
+        levels.level = level.to_level_filter();
+        levels.apply();
+    }
+}
+
+/// Set the maximum log level according to the `--verbose` and `--quiet` options.
+pub fn set_verbosity(verbose: bool, quiet: bool) {
+    if verbose {
+        set_level(log::Level::Debug);
+    } else if quiet {
+        set_level(log::Level::Warn);
+    } else {
+        set_level(log::Level::Info);
+    }
+}
+
+/// Set the filter overriding the log level per module, eg. `rad_anchor=debug,ethers=warn`.
+pub fn set_filter(filter: Filter) {
+    if let Ok(mut levels) = LEVELS.write() {
+        levels.filter = filter;
+        levels.apply();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone as _;
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(
+            timestamp(Utc.timestamp_millis(0)),
+            "1970-01-01T00:00:00.000Z"
+        );
+        assert_eq!(
+            timestamp(Utc.timestamp_millis(1_000_000_000_123)),
+            "2001-09-09T01:46:40.123Z"
+        );
+        assert_eq!(
+            timestamp(Utc.timestamp_millis(951_782_400_000)),
+            "2000-02-29T00:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn test_json_line() {
+        let fields = vec![("tx", "0x5a6b"), ("note", "a \"quoted\"\nnote")];
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("rad_anchor")
+                .args(format_args!("Transaction \"{}\" pending\nstill", "0x5a6b"))
+                .key_values(&fields)
+                .build(),
+        );
+        assert!(!line.contains('\n'), "a record must be a single line");
+
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["target"], "rad_anchor");
+        assert_eq!(json["message"], "Transaction \"0x5a6b\" pending\nstill");
+        assert_eq!(json["fields"]["tx"], "0x5a6b");
+        assert_eq!(json["fields"]["note"], "a \"quoted\"\nnote");
+
+        let timestamp = json["timestamp"].as_str().unwrap();
+        assert!(DateTime::parse_from_rfc3339(timestamp).is_ok());
+        assert!(timestamp.ends_with('Z'));
+    }
 }
@@ -0,0 +1,175 @@
+//! Log file with size-based rotation.
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Maximum size of a log file before it is rotated, when not configured.
+pub const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+/// Number of rotated log files kept, when not configured.
+pub const DEFAULT_KEEP: usize = 5;
+
+/// Log file, rotated once it would grow past its maximum size.
+///
+/// When rotating, `rad.log` is renamed to `rad.log.1`, `rad.log.1` to `rad.log.2` and so
+/// on, and the oldest file is removed, so that at most `keep` rotated files are kept.
+pub struct LogFile {
+    path: PathBuf,
+    max_size: u64,
+    keep: usize,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    /// Open a log file for appending, creating it and its directory if needed.
+    pub fn open(path: PathBuf, max_size: u64, keep: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = append(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_size,
+            keep,
+            file,
+            size,
+        })
+    }
+
+    /// Write a line to the log file, rotating it first if the line doesn't fit.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::remove_file(rotated(&self.path, self.keep)).ok();
+
+            for n in (1..self.keep).rev() {
+                fs::rename(rotated(&self.path, n), rotated(&self.path, n + 1)).ok();
+            }
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        }
+        self.file = append(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+/// Open a file for appending, creating it if needed.
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Get the path of the `n`th rotated log file, eg. `rad.log.1`.
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(format!(".{}", n));
+
+    PathBuf::from(path)
+}
+
+/// Parse a file size in bytes, with an optional `K`, `M` or `G` suffix, eg. `10M`.
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, unit) = match s.char_indices().last()? {
+        (i, 'K') | (i, 'k') => (&s[..i], 1024),
+        (i, 'M') | (i, 'm') => (&s[..i], 1024 * 1024),
+        (i, 'G') | (i, 'g') => (&s[..i], 1024 * 1024 * 1024),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// Remove the terminal escape sequences of colors and hyperlinks from a message.
+pub fn strip_ansi(msg: &str) -> String {
+    let mut result = String::with_capacity(msg.len());
+    let mut chars = msg.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control sequence, eg. a color, ending with a byte in the range `@` to `~`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system command, eg. a hyperlink, ending with `BEL` or `ESC \`.
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_rotate() {
+        let dir = env::temp_dir().join(format!("radicle-tools-log-{}", process::id()));
+        let path = dir.join("rad.log");
+        let mut file = LogFile::open(path.clone(), 8, 2).unwrap();
+
+        for line in &["one", "two", "three", "four"] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "four\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), "three\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 2)).unwrap(), "one\ntwo\n");
+        assert!(!rotated(&path, 3).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("10M"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_size("2k"), Some(2048));
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("ten"), None);
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\x1b[1;33mabc\x1b[0m \x1b]8;;https://etherscan.io\x1b\\0x1a\x1b]8;;\x1b\\"),
+            "abc 0x1a"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Per-module log filters.
+use std::str::FromStr;
+
+use log::LevelFilter;
+
+/// Log filter, made of comma-separated directives, eg. `rad_anchor=debug,ethers=warn`.
+///
+/// A directive is either `<module>=<level>`, which applies to records of the module and its
+/// submodules, or a bare `<level>`, which applies to all other records. When several
+/// directives match a record, the one of the innermost module applies.
+///
+/// A crate name also applies to the crates it is split into, eg. `ethers` applies to
+/// `ethers_providers` and `ethers_signers`, as these are the targets their records have.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    directives: Vec<(Option<String>, LevelFilter)>,
+}
+
+/// Error parsing a log filter.
+#[derive(thiserror::Error, Debug)]
+#[error("invalid log filter directive '{0}'")]
+pub struct FilterError(String);
+
+impl Filter {
+    /// Get the level of the directive applying to the given target, if any.
+    pub fn level(&self, target: &str) -> Option<LevelFilter> {
+        let mut result: Option<(usize, LevelFilter)> = None;
+
+        for (module, level) in &self.directives {
+            let len = match module {
+                Some(module) if is_within(target, module) => module.len() + 1,
+                Some(_) => continue,
+                None => 0,
+            };
+            match result {
+                Some((longest, _)) if len < longest => {}
+                _ => result = Some((len, *level)),
+            }
+        }
+        result.map(|(_, level)| level)
+    }
+
+    /// Get the most verbose level of all directives.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = Vec::new();
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let invalid = || FilterError(directive.to_owned());
+            let (module, level) = match directive.find('=') {
+                Some(i) => {
+                    let module = directive[..i].trim();
+                    if module.is_empty() {
+                        return Err(invalid());
+                    }
+                    // Crate names are accepted as written in manifests, eg. `rad-anchor`.
+                    (Some(module.replace('-', "_")), &directive[i + 1..])
+                }
+                None => (None, directive),
+            };
+            let level = level.trim().parse().map_err(|_| invalid())?;
+
+            directives.push((module, level));
+        }
+        Ok(Self { directives })
+    }
+}
+
+/// Check whether a record target is the given module, or one of its submodules.
+///
+/// A crate name, eg. `ethers`, also matches the crates prefixed with it, eg. `ethers_core`.
+fn is_within(target: &str, module: &str) -> bool {
+    match target.strip_prefix(module) {
+        Some(rest) => {
+            rest.is_empty()
+                || rest.starts_with("::")
+                || (!module.contains("::") && rest.starts_with('_'))
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter: Filter = "warn, rad-anchor=debug, ethers=error, ethers::providers=trace"
+            .parse()
+            .unwrap();
+
+        assert_eq!(filter.level("rad_anchor"), Some(LevelFilter::Debug));
+        assert_eq!(filter.level("rad_anchor::git"), Some(LevelFilter::Debug));
+        assert_eq!(filter.level("rad_anchorage"), Some(LevelFilter::Warn));
+        assert_eq!(filter.level("ethers::contract"), Some(LevelFilter::Error));
+        assert_eq!(
+            filter.level("ethers::providers::http"),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        let filter: Filter = "ethers=warn".parse().unwrap();
+        assert_eq!(filter.level("rad_org"), None);
+        assert_eq!(filter.level("ethersx"), None);
+
+        assert!("rad_anchor=loud".parse::<Filter>().is_err());
+        assert!("=debug".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_filter_crate_family() {
+        let filter: Filter = "ethers=debug, ethers_providers::provider=trace, ethers::core=warn"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            filter.level("ethers_providers::provider"),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(
+            filter.level("ethers_providers::transports::http"),
+            Some(LevelFilter::Debug)
+        );
+        assert_eq!(filter.level("ethers_signers"), Some(LevelFilter::Debug));
+        // Only crate names match the crates prefixed with them.
+        assert_eq!(filter.level("ethers::core"), Some(LevelFilter::Warn));
+        assert_eq!(filter.level("ethers::core_utils"), Some(LevelFilter::Debug));
+    }
+}
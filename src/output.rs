@@ -0,0 +1,211 @@
+//! Terminal output styling.
+//!
+//! All colored output of the tools goes through this module, so that it can be turned off
+//! in one place.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fmt};
+
+use colored::{ColoredString, Colorize};
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::config;
+
+/// Whether links are output as terminal hyperlinks.
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Decide whether output should be colored, and whether links should be hyperlinks.
+///
+/// Colors are forced on when `CLICOLOR_FORCE` is set, and turned off when `NO_COLOR` is set
+/// or when stdout or stderr isn't a terminal.
+///
+/// Hyperlinks are output when stdout and stderr are terminals other than `TERM=dumb`. This is
+/// overridden by setting `FORCE_HYPERLINK` to `1` or `0`.
+pub fn init() {
+    let terminal = atty::is(atty::Stream::Stdout) && atty::is(atty::Stream::Stderr);
+    let force = matches!(env::var("CLICOLOR_FORCE"), Ok(v) if v != "0");
+    let enabled = force || (config::env_var("NO_COLOR").is_none() && terminal);
+
+    colored::control::set_override(enabled);
+
+    let hyperlinks = match env::var("FORCE_HYPERLINK") {
+        Ok(v) => v != "0",
+        Err(_) => terminal && env::var("TERM").map_or(true, |term| term != "dumb"),
+    };
+    HYPERLINKS.store(hyperlinks, Ordering::Relaxed);
+}
+
+/// Style a prompt asking the user for input.
+pub fn prompt(msg: &str) -> String {
+    format!("{} {}", "??".cyan(), msg)
+}
+
+/// Check whether the user can be asked for input, ie. stdin and stderr are terminals.
+pub fn interactive() -> bool {
+    atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr)
+}
+
+/// Ask the user for a value, until one is given that passes validation.
+///
+/// Invalid values are reported and the question is asked again. Fails if stdin is closed
+/// before a valid value is given.
+pub fn input<T, E: fmt::Display>(msg: &str, parse: impl Fn(&str) -> Result<T, E>) -> io::Result<T> {
+    let mut stderr = io::stderr();
+
+    loop {
+        write!(stderr, "{}", prompt(&format!("{}: ", msg)))?;
+        stderr.flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("no value given for '{}'", msg.to_lowercase()),
+            ));
+        }
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            continue;
+        }
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(err) => writeln!(stderr, "{}", negative(&format!("Invalid value: {}", err)))?,
+        }
+    }
+}
+
+/// Style a hint on how to resolve an error.
+pub fn hint(msg: &str) -> String {
+    format!("{} {}", "hint:".cyan(), msg)
+}
+
+/// Ask the user to confirm an action, unless it was confirmed up-front, eg. with `--yes`.
+///
+/// Fails if the user would have to be asked but stdin isn't a terminal.
+pub fn confirm(msg: &str, yes: bool) -> io::Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "confirmation required but stdin is not a terminal, use '--yes' to confirm",
+        ));
+    }
+
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", prompt(&format!("{} [y/N] ", msg)))?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Ask the user for a password, without echoing it.
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if stdin or stderr isn't a terminal.
+pub fn password(msg: &str) -> io::Result<String> {
+    if !interactive() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "password required but stdin is not a terminal",
+        ));
+    }
+    rpassword::prompt_password_stderr(&prompt(&format!("{}: ", msg)))
+}
+
+/// A spinner shown on stderr while a long-running operation is in progress.
+///
+/// The spinner is cleared when finished or dropped, so an early return doesn't leave it
+/// behind. Nothing should be logged while a spinner is shown.
+pub struct Spinner {
+    bar: ProgressBar,
+}
+
+impl Spinner {
+    /// Clear the spinner.
+    pub fn finish(self) {}
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Show a spinner with the given message, eg. "Waiting for confirmation..".
+///
+/// Like the logs it replaces, the spinner is hidden when only warnings and errors are output,
+/// or when stderr isn't a terminal.
+pub fn spinner(msg: &str) -> Spinner {
+    if log::max_level() < log::LevelFilter::Info || !atty::is(atty::Stream::Stderr) {
+        return Spinner {
+            bar: ProgressBar::hidden(),
+        };
+    }
+    let bar = ProgressBar::new_spinner();
+    let template = format!("{} {{msg}}", "{spinner}".blue());
+
+    bar.set_style(ProgressStyle::default_spinner().template(&template));
+    bar.set_message(msg.to_owned());
+    bar.enable_steady_tick(100);
+
+    Spinner { bar }
+}
+
+/// Style a positive status, eg. a commit being anchored.
+pub fn positive(msg: &str) -> ColoredString {
+    msg.green()
+}
+
+/// Style a negative status, eg. a commit not being anchored.
+pub fn negative(msg: &str) -> ColoredString {
+    msg.red()
+}
+
+/// Style an identifier, eg. a commit hash.
+pub fn id(msg: &str) -> ColoredString {
+    msg.yellow()
+}
+
+/// Link text to a URL, eg. a transaction hash to the block explorer.
+///
+/// The link is an OSC 8 terminal hyperlink if hyperlinks are enabled, and the plain text
+/// otherwise. The URL is never shown, so it should also be output separately if it matters.
+pub fn link(text: &str, url: &str) -> String {
+    if HYPERLINKS.load(Ordering::Relaxed) {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Style information that should stand out, eg. a value differing from its default.
+pub fn highlight(msg: &str) -> ColoredString {
+    msg.bold()
+}
+
+/// Style secondary information.
+pub fn dim(msg: &str) -> ColoredString {
+    msg.dimmed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link() {
+        HYPERLINKS.store(false, Ordering::Relaxed);
+        assert_eq!(link("0x1a", "https://etherscan.io/tx/0x1a"), "0x1a");
+
+        HYPERLINKS.store(true, Ordering::Relaxed);
+        assert_eq!(
+            link("0x1a", "https://etherscan.io/tx/0x1a"),
+            "\x1b]8;;https://etherscan.io/tx/0x1a\x1b\\0x1a\x1b]8;;\x1b\\"
+        );
+    }
+}
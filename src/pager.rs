@@ -0,0 +1,171 @@
+//! Paging of long listings.
+//!
+//! Like git, listings are piped through `$PAGER` (default: `less`) when stdout is a terminal.
+//! Unless `LESS` is set, `less` is run with `FRX`, so that a listing fitting on one screen is
+//! printed as-is instead of being paged.
+use std::env;
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+
+use crate::interrupt;
+
+/// Pager used when `$PAGER` isn't set.
+pub const DEFAULT_PAGER: &str = "less";
+
+/// Output of a listing, either paged or written to stdout directly.
+pub struct Pager {
+    child: Option<Child>,
+}
+
+impl Pager {
+    /// Start paging, if `enabled` and stdout is a terminal.
+    ///
+    /// Falls back to writing to stdout if the pager can't be started.
+    pub fn start(enabled: bool) -> Self {
+        if !enabled || !atty::is(atty::Stream::Stdout) {
+            return Self { child: None };
+        }
+        let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_owned());
+
+        match command(&pager) {
+            Some(command) => Self::spawn(command),
+            None => Self { child: None },
+        }
+    }
+
+    /// Start the pager command, or fall back to writing to stdout if it can't be started.
+    fn spawn(mut command: Command) -> Self {
+        match command.spawn() {
+            Ok(child) => {
+                interrupt::paging(true);
+                Self { child: Some(child) }
+            }
+            Err(err) => {
+                log::debug!("Failed to start pager {:?}: {}", command, err);
+                Self { child: None }
+            }
+        }
+    }
+
+    /// Wait for the user to quit the pager.
+    ///
+    /// Interrupts are ignored until then, so that Ctrl-C doesn't leave the pager behind.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.wait()
+    }
+
+    fn wait(&mut self) -> io::Result<()> {
+        match self.child.take() {
+            Some(mut child) => {
+                // Close stdin, so that the pager knows the listing is complete.
+                drop(child.stdin.take());
+                let result = child.wait();
+
+                interrupt::paging(false);
+                result?;
+            }
+            None => io::stdout().flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Get the command running the given pager, if paging isn't disabled with an empty pager,
+/// or `cat`.
+fn command(pager: &str) -> Option<Command> {
+    let pager = pager.trim();
+
+    if pager.is_empty() || pager == "cat" {
+        return None;
+    }
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(pager).stdin(Stdio::piped());
+
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    Some(command)
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.child.as_mut().and_then(|child| child.stdin.as_mut()) {
+            Some(stdin) => match stdin.write(buf) {
+                // The user quit the pager before the end of the listing.
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+                result => result,
+            },
+            None => io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.child.as_mut().and_then(|child| child.stdin.as_mut()) {
+            Some(stdin) => match stdin.flush() {
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                result => result,
+            },
+            None => io::stdout().flush(),
+        }
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        self.wait().ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interrupt::tests::STATE;
+
+    #[test]
+    fn test_start_bypass() {
+        let _state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+
+        // Eg. with '--json', or '--no-pager'.
+        assert!(Pager::start(false).child.is_none());
+
+        if !atty::is(atty::Stream::Stdout) {
+            assert!(Pager::start(true).child.is_none());
+        }
+        assert!(!interrupt::is_paging());
+    }
+
+    #[test]
+    fn test_command() {
+        assert!(command("").is_none());
+        assert!(command("  ").is_none());
+        assert!(command("cat").is_none());
+
+        let command = command("less -S").unwrap();
+        assert!(format!("{:?}", command).ends_with(r#""sh" "-c" "less -S""#));
+    }
+
+    #[test]
+    fn test_spawn() {
+        let _state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut command = Command::new("cat");
+        command.stdin(Stdio::piped()).stdout(Stdio::null());
+
+        let mut pager = Pager::spawn(command);
+        assert!(pager.child.is_some());
+        assert!(interrupt::is_paging());
+
+        writeln!(pager, "listing").unwrap();
+        pager.finish().unwrap();
+        assert!(!interrupt::is_paging());
+    }
+
+    #[test]
+    fn test_spawn_failure() {
+        let _state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+
+        let pager = Pager::spawn(Command::new("/nonexistent/pager"));
+        assert!(pager.child.is_none());
+        assert!(!interrupt::is_paging());
+    }
+}
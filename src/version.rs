@@ -0,0 +1,33 @@
+//! Version information.
+
+/// Git commit the tools were built from, if it was known at build time.
+pub const GIT_COMMIT: Option<&str> = option_env!("RADICLE_TOOLS_GIT_COMMIT");
+
+/// Get the long version of a tool, shown by `--version`.
+///
+/// This is the version of the tool, followed by the commit it was built from and any other
+/// details relevant when comparing builds, eg. the chains it supports.
+pub fn long(version: &str, details: &[(&str, String)]) -> String {
+    let mut long = format!("{}\ncommit: {}", version, GIT_COMMIT.unwrap_or("unknown"));
+
+    for (name, value) in details {
+        long.push_str(&format!("\n{}: {}", name, value));
+    }
+    long
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long() {
+        let long = long("0.1.0", &[("chains", "homestead (1)".to_owned())]);
+        let lines: Vec<&str> = long.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "0.1.0");
+        assert!(lines[1].starts_with("commit: "));
+        assert_eq!(lines[2], "chains: homestead (1)");
+    }
+}